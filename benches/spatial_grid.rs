@@ -0,0 +1,125 @@
+//! Stress test for the spatial grid behind the explosion-chain and damage
+//! checks. Spawns thousands of chainable things plus a pile of explosions,
+//! then times the grid against the old check-everything-against-everything
+//! loop.
+//!
+//! Run with `cargo bench --bench spatial_grid`.
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use chain_reaction_jam::spatial::{Hitbox, SpatialGrid, rebuild_grid};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+#[derive(Component)]
+struct Target {
+    hitbox_radius: f32,
+}
+
+impl Hitbox for Target {
+    fn hitbox_radius(&self) -> f32 {
+        self.hitbox_radius
+    }
+}
+
+#[derive(Component)]
+struct Blast {
+    radius: f32,
+}
+
+const TARGET_COUNTS: [usize; 3] = [1_000, 5_000, 20_000];
+const BLAST_COUNT: usize = 200;
+/// A bit bigger than the window, same as the game's play area plus spawn room.
+const HALF_EXTENTS: Vec2 = Vec2::new(400.0, 600.0);
+
+fn random_position(rng: &mut StdRng) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(-HALF_EXTENTS.x..HALF_EXTENTS.x),
+        rng.gen_range(-HALF_EXTENTS.y..HALF_EXTENTS.y),
+        0.0,
+    )
+}
+
+fn stress_world(target_count: usize) -> World {
+    let mut rng = StdRng::seed_from_u64(0xC4A1);
+    let mut world = World::new();
+    world.init_resource::<SpatialGrid<Target>>();
+    for _ in 0..target_count {
+        world.spawn((
+            Target { hitbox_radius: 5.0 },
+            Transform::from_translation(random_position(&mut rng)),
+        ));
+    }
+    for _ in 0..BLAST_COUNT {
+        world.spawn((
+            Blast {
+                radius: rng.gen_range(50.0..120.0),
+            },
+            Transform::from_translation(random_position(&mut rng)),
+        ));
+    }
+    world
+}
+
+fn count_hits_with_grid(
+    blast_query: Query<(&Transform, &Blast)>,
+    grid: Res<SpatialGrid<Target>>,
+) -> usize {
+    blast_query
+        .iter()
+        .map(|(transform, blast)| {
+            grid.query(transform.translation.truncate(), blast.radius)
+                .count()
+        })
+        .sum()
+}
+
+fn count_hits_brute_force(
+    blast_query: Query<(&Transform, &Blast)>,
+    target_query: Query<(&Transform, &Target)>,
+) -> usize {
+    let mut hits = 0;
+    for (blast_transform, blast) in &blast_query {
+        for (target_transform, target) in &target_query {
+            let distance = blast_transform
+                .translation
+                .distance(target_transform.translation);
+            if distance <= blast.radius + target.hitbox_radius {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn bench_proximity_checks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("proximity_checks");
+    for target_count in TARGET_COUNTS {
+        let mut world = stress_world(target_count);
+
+        // both approaches have to agree before timing them means anything
+        world.run_system_once(rebuild_grid::<Target>).unwrap();
+        let grid_hits = world.run_system_once(count_hits_with_grid).unwrap();
+        let brute_force_hits = world.run_system_once(count_hits_brute_force).unwrap();
+        assert_eq!(grid_hits, brute_force_hits);
+
+        group.bench_with_input(
+            BenchmarkId::new("grid", target_count),
+            &target_count,
+            |b, _| {
+                b.iter(|| {
+                    world.run_system_once(rebuild_grid::<Target>).unwrap();
+                    world.run_system_once(count_hits_with_grid).unwrap()
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", target_count),
+            &target_count,
+            |b, _| b.iter(|| world.run_system_once(count_hits_brute_force).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_proximity_checks);
+criterion_main!(benches);
@@ -1,4 +1,11 @@
-use bevy::prelude::*;
+use bevy::{
+    audio::{SpatialScale, Volume},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use rand::Rng;
+
+use crate::{AppSystems, Pause, asset_tracking::LoadResource, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Music>();
@@ -8,6 +15,45 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         apply_global_volume.run_if(resource_changed::<GlobalVolume>),
     );
+
+    app.register_type::<Voice>();
+    app.register_type::<PositionalAudio>();
+    app.init_resource::<PositionalAudio>();
+    app.add_event::<PlaySfx>();
+    app.add_systems(Startup, spawn_listener);
+    app.add_systems(Update, play_sfx.in_set(AppSystems::Update));
+
+    app.register_type::<MusicAssets>();
+    app.load_resource::<MusicAssets>();
+    app.register_type::<Soundtrack>();
+    app.register_type::<MusicIntensity>();
+    app.register_type::<MusicLayer>();
+    app.init_resource::<Soundtrack>();
+    app.init_resource::<MusicIntensity>();
+
+    app.add_systems(OnEnter(Pause(true)), pause_audio);
+    app.add_systems(OnExit(Pause(true)), resume_audio);
+
+    app.register_type::<MuteWhenUnfocused>();
+    app.init_resource::<MuteWhenUnfocused>();
+    app.add_systems(Update, mute_while_unfocused);
+
+    app.add_systems(OnEnter(Screen::Title), play_menu_music);
+    app.add_systems(OnEnter(Screen::Gameplay), play_gameplay_music);
+    app.add_systems(OnEnter(Screen::Tutorial), play_gameplay_music);
+    app.add_systems(
+        Update,
+        (
+            // also catches up once the music has loaded, in case the
+            // soundtrack was picked before then
+            start_soundtrack.run_if(
+                resource_exists::<MusicAssets>
+                    .and(resource_changed::<Soundtrack>.or(resource_added::<MusicAssets>)),
+            ),
+            fade_music,
+        )
+            .chain(),
+    );
 }
 
 /// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
@@ -18,9 +64,13 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 pub struct Music;
 
-/// A music audio instance.
+/// A music audio instance. It starts silent, so it can be faded in.
 pub fn music(handle: Handle<AudioSource>) -> impl Bundle {
-    (AudioPlayer(handle), PlaybackSettings::LOOP, Music)
+    (
+        AudioPlayer(handle),
+        PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        Music,
+    )
 }
 
 /// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
@@ -36,12 +86,423 @@ pub fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
     (AudioPlayer(handle), PlaybackSettings::DESPAWN, SoundEffect)
 }
 
+/// Kinds of sound effect. Each has its own limit on how many can play at
+/// once, so a big chain can't drown out everything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum SfxCategory {
+    Explosion,
+    Shot,
+    BuildingHit,
+    Movement,
+}
+
+impl SfxCategory {
+    fn max_voices(self) -> usize {
+        match self {
+            Self::Explosion => 8,
+            Self::Shot => 4,
+            Self::BuildingHit => 3,
+            Self::Movement => 4,
+        }
+    }
+
+    /// How far pitch and volume can randomly stray either way, as fractions,
+    /// so repeats of the same clip don't sound mechanical.
+    fn variation(self) -> (f32, f32) {
+        match self {
+            Self::Explosion => (0.08, 0.15),
+            Self::Shot => (0.05, 0.1),
+            Self::BuildingHit => (0.05, 0.1),
+            Self::Movement => (0.1, 0.2),
+        }
+    }
+}
+
+/// Plays a sound effect, if its category has a voice free. When it doesn't,
+/// the new sound takes over the least important voice playing, or the oldest
+/// of those, unless everything playing matters more.
+#[derive(Event, Debug, Clone)]
+pub struct PlaySfx {
+    pub handle: Handle<AudioSource>,
+    pub category: SfxCategory,
+    pub priority: f32,
+    /// Playback speed, which also sets the pitch. Random variation is added
+    /// on top.
+    pub pitch: f32,
+    /// Where in the world the sound comes from. Sounds without one play
+    /// centred.
+    pub position: Option<Vec2>,
+}
+
+impl PlaySfx {
+    pub fn new(handle: Handle<AudioSource>, category: SfxCategory) -> Self {
+        Self {
+            handle,
+            category,
+            priority: 0.0,
+            pitch: 1.0,
+            position: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: f32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_pitch(mut self, pitch: f32) -> Self {
+        self.pitch = pitch;
+        self
+    }
+
+    pub fn at(mut self, position: Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+/// Whether sound effects are panned to where they happen. Toggled from the
+/// settings menu, on by default.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct PositionalAudio {
+    pub enabled: bool,
+}
+
+impl Default for PositionalAudio {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// How far apart the listener's ears are, in world units. Out at the edges
+/// of where the cannons can go, so a sound at either edge is right in one
+/// ear.
+const EAR_GAP: f32 = 660.0;
+/// World units to audio units. Sounds fall off with the square of their
+/// distance from each ear past one audio unit, so this puts the ears about one
+/// and a half units out from the middle, where the nearer ear clearly wins.
+const AUDIO_SCALE: f32 = 1.0 / 220.0;
+/// Positional sounds come out quieter overall than centred ones, even up
+/// close, so they get boosted to roughly match.
+const POSITIONAL_GAIN: f32 = 2.0;
+
+/// The listener stays put in the middle of the play area rather than
+/// following the camera, so screen shake doesn't wobble the mix.
+fn spawn_listener(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Audio Listener"),
+        SpatialListener::new(EAR_GAP),
+        Transform::default(),
+    ));
+}
+
+/// A sound effect that's using up one of its category's voices.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+struct Voice {
+    category: SfxCategory,
+    priority: f32,
+    /// In real time.
+    started: f32,
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut ev_play_sfx: EventReader<PlaySfx>,
+    voice_query: Query<(Entity, &Voice)>,
+    positional_audio: Res<PositionalAudio>,
+    time: Res<Time<Real>>,
+) {
+    let mut voices: Vec<(Entity, Voice)> = voice_query
+        .iter()
+        .map(|(entity, voice)| (entity, *voice))
+        .collect();
+    let mut rng = rand::thread_rng();
+    for ev in ev_play_sfx.read() {
+        let playing = voices
+            .iter()
+            .filter(|(_, voice)| voice.category == ev.category)
+            .count();
+        if playing >= ev.category.max_voices() {
+            let Some(index) = voices
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, voice))| voice.category == ev.category)
+                .min_by(|(_, (_, a)), (_, (_, b))| {
+                    a.priority
+                        .total_cmp(&b.priority)
+                        .then(a.started.total_cmp(&b.started))
+                })
+                .map(|(index, _)| index)
+            else {
+                continue;
+            };
+            if voices[index].1.priority > ev.priority {
+                continue;
+            }
+            commands.entity(voices[index].0).try_despawn();
+            voices.swap_remove(index);
+        }
+
+        let (pitch_variation, volume_variation) = ev.category.variation();
+        let pitch = ev.pitch * (1.0 + rng.gen_range(-pitch_variation..=pitch_variation));
+        let mut volume = 1.0 + rng.gen_range(-volume_variation..=volume_variation);
+        let mut playback = PlaybackSettings::DESPAWN.with_speed(pitch);
+        let mut transform = Transform::default();
+        if let Some(position) = ev.position.filter(|_| positional_audio.enabled) {
+            volume *= POSITIONAL_GAIN;
+            playback = playback
+                .with_spatial(true)
+                .with_spatial_scale(SpatialScale::new_2d(AUDIO_SCALE));
+            transform.translation = position.extend(0.0);
+        }
+        let voice = Voice {
+            category: ev.category,
+            priority: ev.priority,
+            started: time.elapsed_secs(),
+        };
+        let entity = commands
+            .spawn((
+                Name::new("Sound Effect"),
+                AudioPlayer(ev.handle.clone()),
+                playback.with_volume(Volume::Linear(volume)),
+                transform,
+                SoundEffect,
+                voice,
+            ))
+            .id();
+        voices.push((entity, voice));
+    }
+}
+
+/// Everything playing when the game pauses stops where it is, music included.
+/// Sounds started while paused, like menu clicks, play as normal.
+fn pause_audio(sink_query: Query<&AudioSink>, spatial_sink_query: Query<&SpatialAudioSink>) {
+    for sink in &sink_query {
+        sink.pause();
+    }
+    for sink in &spatial_sink_query {
+        sink.pause();
+    }
+}
+
+fn resume_audio(sink_query: Query<&AudioSink>, spatial_sink_query: Query<&SpatialAudioSink>) {
+    for sink in &sink_query {
+        sink.play();
+    }
+    for sink in &spatial_sink_query {
+        sink.play();
+    }
+}
+
+/// Whether everything goes quiet while the window doesn't have focus. Toggled
+/// from the settings menu, on by default.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct MuteWhenUnfocused {
+    pub enabled: bool,
+}
+
+impl Default for MuteWhenUnfocused {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Muting keeps track of each sink's volume, so fades and volume changes
+/// still land while muted. Sounds that start while unfocused get muted as
+/// they come in.
+fn mute_while_unfocused(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mute_when_unfocused: Res<MuteWhenUnfocused>,
+    mut sink_query: Query<&mut AudioSink>,
+    mut spatial_sink_query: Query<&mut SpatialAudioSink>,
+) {
+    let muted = mute_when_unfocused.enabled && !window.focused;
+    for mut sink in &mut sink_query {
+        if sink.is_muted() != muted {
+            if muted {
+                sink.mute();
+            } else {
+                sink.unmute();
+            }
+        }
+    }
+    for mut sink in &mut spatial_sink_query {
+        if sink.is_muted() != muted {
+            if muted {
+                sink.mute();
+            } else {
+                sink.unmute();
+            }
+        }
+    }
+}
+
 /// [`GlobalVolume`] doesn't apply to already-running audio entities, so this system will update them.
 fn apply_global_volume(
     global_volume: Res<GlobalVolume>,
-    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink)>,
+    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink), Without<MusicLayer>>,
+    mut spatial_audio_query: Query<(&PlaybackSettings, &mut SpatialAudioSink)>,
 ) {
     for (playback, mut sink) in &mut audio_query {
         sink.set_volume(global_volume.volume * playback.volume);
     }
+    for (playback, mut sink) in &mut spatial_audio_query {
+        sink.set_volume(global_volume.volume * playback.volume);
+    }
+}
+
+/// Which music should be playing. Changing it crossfades to the new one.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum Soundtrack {
+    #[default]
+    Silence,
+    Menu,
+    Gameplay,
+}
+
+/// How hectic gameplay is right now, from 0 (calm) to 1 (flat out). The
+/// gameplay music brings in more layers as it rises.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct MusicIntensity(pub f32);
+
+/// The gameplay music's layers, calmest first: the file, and the intensities
+/// it starts and finishes fading in between. Layers that have to stay in time
+/// with each other should be the same length.
+const GAMEPLAY_LAYERS: [(&str, f32, f32); 2] = [
+    ("audio/music/track_2.mp3", 0.0, 0.0),
+    // a low rumble with no beat, so it can't drift out of time
+    ("audio/music/tension.wav", 0.3, 0.9),
+];
+/// How quickly layers fade, in full volume per second.
+const MUSIC_FADE_SPEED: f32 = 0.8;
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct MusicAssets {
+    #[dependency]
+    menu: Handle<AudioSource>,
+    #[dependency]
+    gameplay: Vec<Handle<AudioSource>>,
+}
+
+impl FromWorld for MusicAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            menu: assets.load("audio/music/track_1.mp3"),
+            gameplay: GAMEPLAY_LAYERS
+                .iter()
+                .map(|(path, _, _)| assets.load(*path))
+                .collect(),
+        }
+    }
+}
+
+/// One layer of a soundtrack, fading towards however loud it should be.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+struct MusicLayer {
+    soundtrack: Soundtrack,
+    /// The intensities it starts and finishes fading in between.
+    fade_in: (f32, f32),
+    volume: f32,
+}
+
+impl MusicLayer {
+    fn new(soundtrack: Soundtrack, fade_in: (f32, f32)) -> Self {
+        Self {
+            soundtrack,
+            fade_in,
+            volume: 0.0,
+        }
+    }
+
+    fn target_volume(&self, intensity: f32) -> f32 {
+        let (start, full) = self.fade_in;
+        if full > start {
+            ((intensity - start) / (full - start)).clamp(0.0, 1.0)
+        } else if intensity >= start {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+fn play_menu_music(mut soundtrack: ResMut<Soundtrack>) {
+    *soundtrack = Soundtrack::Menu;
+}
+
+fn play_gameplay_music(mut soundtrack: ResMut<Soundtrack>) {
+    *soundtrack = Soundtrack::Gameplay;
+}
+
+/// Starts every layer of the new soundtrack at once, so they stay in time.
+/// Whatever was playing before fades out on its own.
+fn start_soundtrack(
+    mut commands: Commands,
+    soundtrack: Res<Soundtrack>,
+    music_assets: Res<MusicAssets>,
+    layer_query: Query<&MusicLayer>,
+) {
+    // switching back before the old layers finished fading out just fades
+    // them back in
+    if layer_query
+        .iter()
+        .any(|layer| layer.soundtrack == *soundtrack)
+    {
+        return;
+    }
+    match *soundtrack {
+        Soundtrack::Silence => {}
+        Soundtrack::Menu => {
+            commands.spawn((
+                Name::new("Menu Music"),
+                music(music_assets.menu.clone()),
+                MusicLayer::new(Soundtrack::Menu, (0.0, 0.0)),
+            ));
+        }
+        Soundtrack::Gameplay => {
+            for (handle, (_, start, full)) in music_assets.gameplay.iter().zip(GAMEPLAY_LAYERS) {
+                commands.spawn((
+                    Name::new("Gameplay Music"),
+                    music(handle.clone()),
+                    MusicLayer::new(Soundtrack::Gameplay, (start, full)),
+                ));
+            }
+        }
+    }
+}
+
+/// Uses real time, so fades carry on through hit-stop.
+fn fade_music(
+    mut commands: Commands,
+    soundtrack: Res<Soundtrack>,
+    intensity: Res<MusicIntensity>,
+    global_volume: Res<GlobalVolume>,
+    time: Res<Time<Real>>,
+    mut layer_query: Query<(Entity, &mut MusicLayer, Option<&mut AudioSink>)>,
+) {
+    let max_step = MUSIC_FADE_SPEED * time.delta_secs();
+    for (entity, mut layer, sink) in &mut layer_query {
+        let playing = layer.soundtrack == *soundtrack;
+        let target = if playing {
+            layer.target_volume(intensity.0)
+        } else {
+            0.0
+        };
+        layer.volume += (target - layer.volume).clamp(-max_step, max_step);
+        if !playing && layer.volume <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(global_volume.volume * Volume::Linear(layer.volume));
+        }
+    }
 }
@@ -1,65 +1,72 @@
+//! Parallax backdrops. Each backdrop is a stack of layers drawn back to front,
+//! each scrolling at its own speed, loaded from
+//! `assets/backgrounds/backdrops.backdrops.ron`. The gameplay backdrop is also
+//! tinted by the time of day, which moves on as the levels go by.
+
+use std::error::Error;
+
 use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
+use serde::Deserialize;
 
-use crate::{AppSystems, asset_tracking::LoadResource};
+use crate::{AppSystems, asset_tracking::LoadResource, demo::field::PlayArea, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<BGLayer1>();
+    app.init_asset::<Backdrops>();
+    app.init_asset_loader::<BackdropsLoader>();
 
     app.register_type::<BGAssets>();
     app.load_resource::<BGAssets>();
-    app.add_systems(Update, move_layer_1_around.in_set(AppSystems::Update));
-}
-
-#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
-#[reflect(Component)]
-struct BGLayer1 {
-    pub speed: f32,
-    pub offset: f32,
-    pub max_offset: f32,
-}
 
-const BG_LAYER_1_Z: f32 = -10.0;
+    app.register_type::<Backdrop>();
+    app.register_type::<ParallaxLayer>();
+    app.register_type::<DayNightTint>();
+    app.register_type::<TimeOfDay>();
+    app.init_resource::<TimeOfDay>();
 
-pub fn bg_layer_1(bg_assets: &BGAssets) -> impl Bundle {
-    (
-        Sprite {
-            image: bg_assets.texture.clone(),
-            image_mode: SpriteImageMode::Tiled {
-                tile_x: true,
-                tile_y: true,
-                stretch_value: 8.0,
-            },
-            custom_size: Some(Vec2::splat(1200.0)),
-            ..default()
-        },
-        Transform::from_translation(Vec2::ZERO.extend(BG_LAYER_1_Z)),
-        BGLayer1 {
-            speed: 10.0,
-            offset: 0.0,
-            max_offset: 128.0,
-        },
-    )
+    app.add_observer(spawn_backdrop_layers);
+    app.add_systems(OnEnter(Screen::Gameplay), reset_time_of_day);
+    app.add_systems(
+        Update,
+        (
+            scroll_parallax_layers,
+            advance_time_of_day,
+            apply_day_night_tint,
+        )
+            .chain()
+            .in_set(AppSystems::Update),
+    );
 }
 
-const BG_LAYER_2_Z: f32 = -9.0;
+/// Which backdrop to draw. Spawn one with [`backdrop`] and its layers are
+/// filled in from the backdrops file.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum Backdrop {
+    Menu,
+    Gameplay,
+}
 
-pub fn bg_layer_2(bg_assets: &BGAssets) -> impl Bundle {
+/// A backdrop behind everything else. Scope it to whatever screen or menu it
+/// belongs to.
+pub fn backdrop(backdrop: Backdrop) -> impl Bundle {
     (
-        Sprite {
-            image: bg_assets.play_field_background.clone(),
-            ..default()
-        },
-        Transform::from_translation(Vec2::ZERO.extend(BG_LAYER_2_Z)),
+        Name::new("Backdrop"),
+        backdrop,
+        Transform::default(),
+        Visibility::default(),
     )
 }
 
-const BG_LAYER_3_Z: f32 = -8.0;
+const PLAY_FIELD_FRAME_Z: f32 = -8.0;
 
-pub fn bg_layer_3(bg_assets: &BGAssets) -> impl Bundle {
+/// The frame around the play field, drawn in front of the backdrop.
+pub fn play_field_frame(bg_assets: &BGAssets, play_area: &PlayArea) -> impl Bundle {
     (
+        Name::new("Play Field Frame"),
         Sprite {
             image: bg_assets.play_field_frame.clone(),
             image_mode: SpriteImageMode::Sliced(TextureSlicer {
@@ -68,35 +75,150 @@ pub fn bg_layer_3(bg_assets: &BGAssets) -> impl Bundle {
                 sides_scale_mode: SliceScaleMode::Stretch,
                 max_corner_scale: 4.0,
             }),
-            custom_size: Some(Vec2 { x: 760.0, y: 600.0 }),
+            custom_size: Some(play_area.frame_size),
             ..default()
         },
-        Transform::from_translation(Vec2::ZERO.extend(BG_LAYER_3_Z)),
+        Transform::from_translation(Vec2::ZERO.extend(PLAY_FIELD_FRAME_Z)),
     )
 }
 
+/// Every backdrop, as loaded from the backdrops file.
+#[derive(Asset, TypePath, Debug)]
+pub struct Backdrops {
+    menu: Vec<LayerDef>,
+    gameplay: Vec<LayerDef>,
+}
+
+impl Backdrops {
+    fn layers(&self, backdrop: Backdrop) -> &[LayerDef] {
+        match backdrop {
+            Backdrop::Menu => &self.menu,
+            Backdrop::Gameplay => &self.gameplay,
+        }
+    }
+}
+
+/// One layer of a backdrop, with its image loaded. See the backdrops file for
+/// what each field means.
+#[derive(Debug)]
+struct LayerDef {
+    image: Handle<Image>,
+    depth: f32,
+    size: Option<Vec2>,
+    tile: Option<f32>,
+    velocity: Vec2,
+    wrap: Vec2,
+    color: Color,
+    day_night: bool,
+}
+
+/// How the backdrops file is written, before any images are loaded.
+#[derive(Deserialize, Debug)]
+struct SerializedBackdrops {
+    menu: Vec<SerializedLayer>,
+    gameplay: Vec<SerializedLayer>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+struct SerializedLayer {
+    image: String,
+    depth: f32,
+    size: Option<(f32, f32)>,
+    tile: Option<f32>,
+    velocity: (f32, f32),
+    wrap: (f32, f32),
+    color: (f32, f32, f32, f32),
+    day_night: bool,
+}
+
+impl Default for SerializedLayer {
+    fn default() -> Self {
+        Self {
+            image: String::new(),
+            depth: -10.0,
+            size: None,
+            tile: None,
+            velocity: (0.0, 0.0),
+            wrap: (0.0, 0.0),
+            color: (1.0, 1.0, 1.0, 1.0),
+            day_night: false,
+        }
+    }
+}
+
+impl SerializedLayer {
+    fn load(self, load_context: &mut LoadContext) -> LayerDef {
+        let (r, g, b, a) = self.color;
+        LayerDef {
+            image: load_context
+                .loader()
+                .with_settings(|settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                })
+                .load(self.image),
+            depth: self.depth,
+            size: self.size.map(Vec2::from),
+            tile: self.tile,
+            velocity: self.velocity.into(),
+            wrap: self.wrap.into(),
+            color: Color::srgba(r, g, b, a),
+            day_night: self.day_night,
+        }
+    }
+}
+
+#[derive(Default)]
+struct BackdropsLoader;
+
+impl AssetLoader for BackdropsLoader {
+    type Asset = Backdrops;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let backdrops: SerializedBackdrops = ron::de::from_bytes(&bytes)?;
+        Ok(Backdrops {
+            menu: backdrops
+                .menu
+                .into_iter()
+                .map(|layer| layer.load(load_context))
+                .collect(),
+            gameplay: backdrops
+                .gameplay
+                .into_iter()
+                .map(|layer| layer.load(load_context))
+                .collect(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["backdrops.ron"]
+    }
+}
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct BGAssets {
     #[dependency]
-    texture: Handle<Image>,
+    backdrops: Handle<Backdrops>,
     #[dependency]
     play_field_frame: Handle<Image>,
-    #[dependency]
-    play_field_background: Handle<Image>,
 }
 
 impl FromWorld for BGAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
         Self {
-            texture: assets.load_with_settings(
-                "images/tiled_bg.png",
-                |settings: &mut ImageLoaderSettings| {
-                    // Use `nearest` image sampling to preserve pixel art style.
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
+            backdrops: assets.load("backgrounds/backdrops.backdrops.ron"),
             play_field_frame: assets.load_with_settings(
                 "images/game_frame_1.png",
                 |settings: &mut ImageLoaderSettings| {
@@ -104,26 +226,162 @@ impl FromWorld for BGAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
-            play_field_background: assets.load_with_settings(
-                "images/temp_bg_field.png",
-                |settings: &mut ImageLoaderSettings| {
-                    // Use `nearest` image sampling to preserve pixel art style.
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
         }
     }
 }
 
-fn move_layer_1_around(mut layer1_query: Query<(&mut Transform, &mut BGLayer1)>, time: Res<Time>) {
-    for (mut transform, mut bg_layer_1) in &mut layer1_query {
-        let step = bg_layer_1.speed * time.delta_secs();
-        bg_layer_1.offset = if bg_layer_1.offset + step >= bg_layer_1.max_offset {
-            0.0
-        } else {
-            bg_layer_1.offset + step
-        };
-        transform.translation.x = bg_layer_1.offset;
-        transform.translation.y = bg_layer_1.offset;
+/// A layer that scrolls by `velocity` pixels per second, jumping back once
+/// it has moved `wrap` pixels so a tiled image looks like it goes on forever.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+struct ParallaxLayer {
+    velocity: Vec2,
+    wrap: Vec2,
+    offset: Vec2,
+}
+
+/// A layer that darkens and changes color with the [`TimeOfDay`]. `base` is
+/// its color at midday.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+struct DayNightTint {
+    base: Color,
+}
+
+fn spawn_backdrop_layers(
+    trigger: Trigger<OnAdd, Backdrop>,
+    backdrop_query: Query<&Backdrop>,
+    mut commands: Commands,
+    bg_assets: Res<BGAssets>,
+    backdrops: Res<Assets<Backdrops>>,
+) {
+    let Ok(backdrop) = backdrop_query.get(trigger.target()) else {
+        return;
+    };
+    let Some(backdrops) = backdrops.get(&bg_assets.backdrops) else {
+        return;
+    };
+    for layer_def in backdrops.layers(*backdrop) {
+        let mut layer = commands.spawn((layer(layer_def), ChildOf(trigger.target())));
+        if layer_def.day_night {
+            layer.insert(DayNightTint {
+                base: layer_def.color,
+            });
+        }
+    }
+}
+
+fn layer(layer: &LayerDef) -> impl Bundle {
+    (
+        Name::new("Backdrop Layer"),
+        Sprite {
+            image: layer.image.clone(),
+            color: layer.color,
+            image_mode: match layer.tile {
+                Some(stretch_value) => SpriteImageMode::Tiled {
+                    tile_x: true,
+                    tile_y: true,
+                    stretch_value,
+                },
+                None => SpriteImageMode::Auto,
+            },
+            custom_size: layer.size,
+            ..default()
+        },
+        Transform::from_translation(Vec2::ZERO.extend(layer.depth)),
+        ParallaxLayer {
+            velocity: layer.velocity,
+            wrap: layer.wrap,
+            offset: Vec2::ZERO,
+        },
+    )
+}
+
+fn scroll_parallax_layers(mut query: Query<(&mut Transform, &mut ParallaxLayer)>, time: Res<Time>) {
+    for (mut transform, mut layer) in &mut query {
+        let step = layer.velocity * time.delta_secs();
+        let offset = layer.offset + step;
+        // a wrap of zero means the layer never jumps back
+        layer.offset = Vec2::new(
+            if layer.wrap.x > 0.0 {
+                offset.x.rem_euclid(layer.wrap.x)
+            } else {
+                offset.x
+            },
+            if layer.wrap.y > 0.0 {
+                offset.y.rem_euclid(layer.wrap.y)
+            } else {
+                offset.y
+            },
+        );
+        transform.translation.x = layer.offset.x;
+        transform.translation.y = layer.offset.y;
+    }
+}
+
+/// How far through the day the gameplay backdrop is, in days. Every level
+/// moves it on a little, and it catches up gradually rather than jumping.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct TimeOfDay {
+    current: f32,
+    target: f32,
+}
+
+impl TimeOfDay {
+    /// Moves the clock on to `level`. Never winds it back, so in versus the
+    /// field that's furthest along sets the time.
+    pub fn reach_level(&mut self, level: usize) {
+        let target = level.saturating_sub(1) as f32 / LEVELS_PER_DAY;
+        self.target = self.target.max(target);
+    }
+
+    fn tint(&self) -> Color {
+        let t = self.current.fract() * DAY_TINTS.len() as f32;
+        let index = t as usize % DAY_TINTS.len();
+        let next = (index + 1) % DAY_TINTS.len();
+        let (r, g, b) = DAY_TINTS[index];
+        let (next_r, next_g, next_b) = DAY_TINTS[next];
+        Color::srgb(r, g, b).mix(&Color::srgb(next_r, next_g, next_b), t.fract())
+    }
+}
+
+/// Levels from one dawn to the next.
+const LEVELS_PER_DAY: f32 = 8.0;
+/// How quickly the clock catches up with the level, in days per second.
+const TIME_OF_DAY_SPEED: f32 = 0.05;
+/// The tint at evenly spaced times through the day, starting in the morning
+/// and blended in between.
+const DAY_TINTS: [(f32, f32, f32); 5] = [
+    (1.0, 1.0, 1.0),
+    (1.0, 0.97, 0.9),
+    (1.0, 0.7, 0.55),
+    (0.4, 0.45, 0.75),
+    (0.85, 0.72, 0.85),
+];
+
+fn reset_time_of_day(mut time_of_day: ResMut<TimeOfDay>) {
+    *time_of_day = TimeOfDay::default();
+}
+
+fn advance_time_of_day(mut time_of_day: ResMut<TimeOfDay>, time: Res<Time>) {
+    let step = TIME_OF_DAY_SPEED * time.delta_secs();
+    time_of_day.current = (time_of_day.current + step).min(time_of_day.target);
+}
+
+fn apply_day_night_tint(
+    time_of_day: Res<TimeOfDay>,
+    mut query: Query<(&mut Sprite, &DayNightTint)>,
+) {
+    let tint = time_of_day.tint().to_linear();
+    for (mut sprite, day_night) in &mut query {
+        let base = day_night.base.to_linear();
+        sprite.color = LinearRgba::new(
+            base.red * tint.red,
+            base.green * tint.green,
+            base.blue * tint.blue,
+            base.alpha,
+        )
+        .into();
     }
 }
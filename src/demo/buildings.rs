@@ -3,55 +3,97 @@ use bevy::{
     prelude::*,
 };
 
-use super::explosions::Explosion;
+use super::explosions::{Explosion, ExplosionAssets};
+use super::field::{PlayArea, PlayField};
 use super::floating_text::NewText;
-use super::movement::MAX_X;
-use crate::{AppSystems, PausableSystems, asset_tracking::LoadResource, screens::Screen};
-use rand::Rng;
+use super::mode::{GameMode, RunClock};
+use super::particles::{NewParticles, ParticleEffect};
+use super::rng::GameRng;
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::{PlaySfx, SfxCategory},
+    screens::Screen,
+};
+use chain_reaction_jam::spatial::{self, Hitbox, SpatialGrid, rebuild_grid};
+use rand::{Rng, rngs::StdRng};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Building>();
+    app.register_type::<Destroyed>();
+    app.register_type::<Collapsing>();
+    app.add_event::<BuildingDamaged>();
+    app.add_plugins(spatial::plugin::<Building>);
 
     app.register_type::<BuildingAssets>();
     app.load_resource::<BuildingAssets>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
+            rebuild_grid::<Building>,
             check_for_explosion_damage,
             update_building_sprite,
-            check_for_game_over.run_if(in_state(Screen::Gameplay)),
+            // puzzles and versus matches are won or lost in their own modules
+            check_for_game_over.run_if(
+                in_state(Screen::Gameplay)
+                    .and(not(resource_equals(GameMode::Puzzle)))
+                    .and(not(resource_equals(GameMode::Versus))),
+            ),
         )
             .chain()
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        Update,
+        (animate_collapse, smoulder)
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        play_building_hit_sounds
+            .run_if(resource_exists::<ExplosionAssets>)
+            .in_set(AppSystems::Update),
+    );
 }
 
 const MIN_BUILDING_SEPARATION: f32 = 100.0;
 const INITIAL_BUILDING_COUNT: usize = 5;
+const SURVIVAL_BUILDING_COUNT: usize = 1;
+/// Per side. Versus fields are narrower, so fewer buildings fit.
+const VERSUS_BUILDING_COUNT: usize = 3;
+
+fn building_count(game_mode: GameMode) -> usize {
+    match game_mode {
+        GameMode::Endless | GameMode::TimeAttack | GameMode::Daily => INITIAL_BUILDING_COUNT,
+        GameMode::Survival => SURVIVAL_BUILDING_COUNT,
+        GameMode::Versus => VERSUS_BUILDING_COUNT,
+        GameMode::Puzzle => 0,
+    }
+}
 
-fn generate_positions() -> Vec<f32> {
-    let mut rng = rand::thread_rng();
-    let mut positions = Vec::new();
+fn generate_positions(count: usize, field: &PlayField, rng: &mut StdRng) -> Vec<f32> {
+    let mut positions: Vec<f32> = Vec::new();
     let max_attempts = 10_000;
 
     for _ in 0..max_attempts {
-        if positions.len() == INITIAL_BUILDING_COUNT {
+        if positions.len() == count {
             break;
         }
 
-        let candidate = rng.gen_range(-MAX_X..=MAX_X);
+        let candidate = rng.gen_range(field.min_x()..=field.max_x());
 
         if positions
             .iter()
-            .all(|&p| ((p - candidate) as f32).abs() >= MIN_BUILDING_SEPARATION)
+            .all(|&p| (p - candidate).abs() >= MIN_BUILDING_SEPARATION)
         {
             positions.push(candidate);
         }
     }
 
-    if positions.len() < INITIAL_BUILDING_COUNT {
+    if positions.len() < count {
         panic!("Could not generate enough positions with required separation");
     }
 
@@ -59,23 +101,35 @@ fn generate_positions() -> Vec<f32> {
 }
 
 pub fn spawn_buildings(
+    game_mode: GameMode,
+    play_area: &PlayArea,
     building_assets: &BuildingAssets,
+    game_rng: &mut GameRng,
     texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
     commands: &mut Commands,
 ) {
     let layout = TextureAtlasLayout::from_grid(UVec2 { x: 32, y: 64 }, 3, 1, None, None);
     let texture_atlas = texture_atlas_layouts.add(layout);
-    // get INITIAL_BUILDING_COUNT randomly spaced values between -MAX_X and MAX_X
-    // ensure all x vals are at least MIN_BUILDING_SEPARATION apart
-    let x_vals = generate_positions();
+    for field in PlayField::for_mode(game_mode, play_area) {
+        // get a randomly spaced value within the field for each building the mode wants
+        // ensure all x vals are at least MIN_BUILDING_SEPARATION apart
+        let x_vals = generate_positions(building_count(game_mode), &field, &mut game_rng.layout);
 
-    for x in x_vals {
-        commands.spawn(building(x, building_assets, texture_atlas.clone()));
+        for x in x_vals {
+            commands.spawn((
+                building(
+                    Vec2::new(x, play_area.building_y),
+                    building_assets,
+                    texture_atlas.clone(),
+                ),
+                field,
+            ));
+        }
     }
 }
 
 pub fn building(
-    x: f32,
+    position: Vec2,
     building_assets: &BuildingAssets,
     layout: Handle<TextureAtlasLayout>,
 ) -> impl Bundle {
@@ -85,9 +139,9 @@ pub fn building(
             texture_atlas: Some(TextureAtlas { layout, index: 0 }),
             ..default()
         },
-        Transform::from_xyz(x, -160.0, -1.0).with_scale(Vec3::splat(2.0)),
+        Transform::from_translation(position.extend(-1.0)).with_scale(Vec3::splat(BUILDING_SCALE)),
         Building {
-            health: 3,
+            health: BUILDING_MAX_HEALTH,
             last_damage_time: 0.0,
         },
         StateScoped(Screen::Gameplay),
@@ -96,11 +150,37 @@ pub fn building(
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
 #[reflect(Component)]
-struct Building {
+pub struct Building {
     pub health: i32,
     pub last_damage_time: f32,
 }
 
+impl Hitbox for Building {
+    fn hitbox_radius(&self) -> f32 {
+        BUILDING_RADIUS
+    }
+}
+
+/// A building that's been knocked down. It stays on the field as rubble.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct Destroyed;
+
+/// A building partway through falling down.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+struct Collapsing {
+    elapsed: f32,
+    x: f32,
+}
+
+/// Sent whenever a building loses health.
+#[derive(Event)]
+pub struct BuildingDamaged {
+    /// Where the blast hit it.
+    pub position: Vec2,
+}
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct BuildingAssets {
@@ -123,14 +203,34 @@ impl FromWorld for BuildingAssets {
     }
 }
 
+pub const BUILDING_MAX_HEALTH: i32 = 3;
+const BUILDING_SCALE: f32 = 2.0;
+const BUILDING_SPRITE_HEIGHT: f32 = 64.0;
+const COLLAPSE_SECS: f32 = 0.9;
+/// How tall the rubble is, as a scale on the building sprite.
+const RUBBLE_SCALE_Y: f32 = 0.4;
+const RUBBLE_COLOR: Color = Color::srgb(0.45, 0.4, 0.4);
+const COLLAPSE_WOBBLE: f32 = 3.0;
+
 fn update_building_sprite(
-    mut query: Query<(&mut Sprite, &Building, Entity)>,
+    mut query: Query<(&mut Sprite, &Building, &Transform, Entity), Without<Destroyed>>,
     mut commands: Commands,
+    mut ev_new_particles: EventWriter<NewParticles>,
 ) {
-    for (mut sprite, building, entity) in query.iter_mut() {
-        // if building health is 0, destroy the entity
+    for (mut sprite, building, transform, entity) in query.iter_mut() {
+        // if building health is 0, knock it down
         if building.health <= 0 {
-            commands.entity(entity).despawn();
+            ev_new_particles.write(NewParticles::new(
+                ParticleEffect::BuildingDestroyed,
+                transform.translation.truncate(),
+            ));
+            commands.entity(entity).insert((
+                Destroyed,
+                Collapsing {
+                    elapsed: 0.0,
+                    x: transform.translation.x,
+                },
+            ));
         }
         // if building health is less than 3, change sprite to frame 2
         if building.health < 3 {
@@ -148,18 +248,21 @@ const TIME_BETWEEN_DAMAGE: f32 = 2.0;
 
 fn check_for_explosion_damage(
     explosion_query: Query<(&Transform, &Explosion)>,
-    mut building_query: Query<(&Transform, &mut Building)>,
-    time: Res<Time>,
+    mut building_query: Query<(&Transform, &mut Building), Without<Destroyed>>,
+    grid: Res<SpatialGrid<Building>>,
+    run_clock: Res<RunClock>,
     mut ev_new_text: EventWriter<NewText>,
+    mut ev_building_damaged: EventWriter<BuildingDamaged>,
+    mut ev_new_particles: EventWriter<NewParticles>,
 ) {
     for (explosion_transform, explosion) in &explosion_query {
-        for (buidling_transform, mut building) in &mut building_query {
-            let dist_to_explosion = explosion_transform
-                .translation
-                .distance(buidling_transform.translation);
+        for entity in grid.query(explosion_transform.translation.truncate(), explosion.radius) {
+            let Ok((building_transform, mut building)) = building_query.get_mut(entity) else {
+                continue;
+            };
             let enough_time_elapsed =
-                (time.elapsed_secs() - building.last_damage_time) > TIME_BETWEEN_DAMAGE;
-            if (dist_to_explosion - BUILDING_RADIUS) < explosion.radius && enough_time_elapsed {
+                (run_clock.0 - building.last_damage_time) > TIME_BETWEEN_DAMAGE;
+            if enough_time_elapsed {
                 let random_text_offset = Vec2::new(
                     rand::random::<f32>() * 60.0 - 30.0,
                     rand::random::<f32>() * 60.0 - 30.0,
@@ -170,12 +273,43 @@ fn check_for_explosion_damage(
                     explosion_transform.translation.y + random_text_offset.y,
                 ));
                 building.health -= 1;
-                building.last_damage_time = time.elapsed_secs();
+                building.last_damage_time = run_clock.0;
+                // sparks fly off the side of the building facing the blast
+                let building_position = building_transform.translation.truncate();
+                let towards_blast = explosion_transform.translation.truncate() - building_position;
+                let hit_position =
+                    building_position + towards_blast.clamp_length_max(BUILDING_RADIUS);
+                ev_building_damaged.write(BuildingDamaged {
+                    position: hit_position,
+                });
+                ev_new_particles
+                    .write(NewParticles::new(ParticleEffect::BuildingHit, hit_position));
             }
         }
     }
 }
 
+/// Building hits reuse the booms, slowed down into a heavier thud.
+const BUILDING_HIT_PITCH: f32 = 0.6;
+
+fn play_building_hit_sounds(
+    mut ev_building_damaged: EventReader<BuildingDamaged>,
+    explosion_assets: Res<ExplosionAssets>,
+    mut ev_play_sfx: EventWriter<PlaySfx>,
+) {
+    for ev in ev_building_damaged.read() {
+        let boom_index = rand::thread_rng().gen_range(0..explosion_assets.booms.len());
+        ev_play_sfx.write(
+            PlaySfx::new(
+                explosion_assets.booms[boom_index].clone(),
+                SfxCategory::BuildingHit,
+            )
+            .with_pitch(BUILDING_HIT_PITCH)
+            .at(ev.position),
+        );
+    }
+}
+
 fn pick_random_ouch_string() -> String {
     let ouch_strings = [
         "ouch",
@@ -190,12 +324,72 @@ fn pick_random_ouch_string() -> String {
     ouch_strings[rand::random::<usize>() % ouch_strings.len()].to_string()
 }
 
+/// Sinks the building into the ground with a wobble, then leaves the rubble.
+fn animate_collapse(
+    mut query: Query<(&mut Collapsing, &mut Transform, &mut Sprite, Entity)>,
+    mut commands: Commands,
+    time: Res<Time>,
+    play_area: Res<PlayArea>,
+) {
+    // where the bottom of a building sits
+    let ground_y = play_area.building_y - BUILDING_SPRITE_HEIGHT * BUILDING_SCALE / 2.0;
+    for (mut collapsing, mut transform, mut sprite, entity) in &mut query {
+        collapsing.elapsed += time.delta_secs();
+        let t = (collapsing.elapsed / COLLAPSE_SECS).min(1.0);
+        let scale_y =
+            BUILDING_SCALE.lerp(RUBBLE_SCALE_Y, EaseFunction::QuadraticIn.sample_clamped(t));
+        transform.scale.y = scale_y;
+        // keep the bottom on the ground as it shrinks
+        transform.translation.y = ground_y + BUILDING_SPRITE_HEIGHT * scale_y / 2.0;
+        transform.translation.x =
+            collapsing.x + (collapsing.elapsed * 40.0).sin() * COLLAPSE_WOBBLE * (1.0 - t);
+        sprite.color = Color::WHITE.mix(&RUBBLE_COLOR, t);
+        if t >= 1.0 {
+            transform.translation.x = collapsing.x;
+            commands.entity(entity).remove::<Collapsing>();
+        }
+    }
+}
+
+/// How often falling buildings puff smoke and badly damaged ones flicker.
+const SMOULDER_INTERVAL: f32 = 0.12;
+/// Buildings on this much health or less are on fire.
+const ON_FIRE_HEALTH: i32 = 1;
+
+/// Smoke rises off collapsing buildings, and badly damaged ones burn.
+fn smoulder(
+    query: Query<(&Building, &Transform, Has<Collapsing>, Has<Destroyed>)>,
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+    mut ev_new_particles: EventWriter<NewParticles>,
+) {
+    *since_last += time.delta_secs();
+    if *since_last < SMOULDER_INTERVAL {
+        return;
+    }
+    *since_last = 0.0;
+    let half_width = BUILDING_RADIUS / 2.0;
+    for (building, transform, collapsing, destroyed) in &query {
+        let spot = transform.translation.truncate()
+            + Vec2::new(
+                rand::random::<f32>() * 2.0 * half_width - half_width,
+                rand::random::<f32>() * half_width,
+            );
+        if collapsing {
+            ev_new_particles.write(NewParticles::new(ParticleEffect::BuildingSmoke, spot));
+        } else if !destroyed && building.health <= ON_FIRE_HEALTH {
+            ev_new_particles.write(NewParticles::new(ParticleEffect::BuildingFire, spot));
+        }
+    }
+}
+
 fn check_for_game_over(
-    building_query: Query<&Building>,
+    building_query: Query<(), (With<Building>, Without<Destroyed>)>,
+    game_mode: Res<GameMode>,
+    run_clock: Res<RunClock>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
-    let building_count = building_query.iter().count();
-    if building_count == 0 {
+    if building_query.is_empty() || run_clock.is_out_of_time(*game_mode) {
         next_screen.set(Screen::GameOver);
     }
 }
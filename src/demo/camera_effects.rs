@@ -0,0 +1,210 @@
+//! Camera feedback: trauma-based screen shake and hit-stop.
+//!
+//! Explosions and building hits add trauma, which decays over time. The camera
+//! shakes by trauma squared, so small bumps barely register but big ones
+//! really rattle. Long chains briefly freeze the game (hit-stop) to sell how
+//! big they are.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    buildings::BuildingDamaged, enemies::ENEMY_EXPLOSION_RADIUS, explosions::Explosion,
+    score::ScoreEvent,
+};
+use crate::{
+    AppSystems,
+    net::NetSession,
+    screens::{Screen, in_play},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ScreenShake>();
+    app.register_type::<ShakeLevel>();
+    app.register_type::<HitStop>();
+    app.init_resource::<ScreenShake>();
+    app.init_resource::<ShakeLevel>();
+    app.init_resource::<HitStop>();
+
+    app.add_observer(count_chain_links);
+    app.add_systems(
+        Update,
+        (
+            add_explosion_trauma,
+            add_building_damage_trauma,
+            apply_screen_shake,
+            end_chain,
+            end_hit_stop,
+        )
+            .chain()
+            .run_if(in_play)
+            .in_set(AppSystems::Update),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_camera_effects);
+    app.add_systems(OnExit(Screen::Tutorial), reset_camera_effects);
+}
+
+/// How much the screen shakes. Picked in the settings menu, for anyone who
+/// finds it uncomfortable.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum ShakeLevel {
+    Off,
+    Reduced,
+    #[default]
+    Full,
+}
+
+impl ShakeLevel {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Reduced, Self::Full];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Reduced => "Reduced",
+            Self::Full => "Full",
+        }
+    }
+
+    fn multiplier(self) -> f32 {
+        match self {
+            Self::Off => 0.0,
+            Self::Reduced => 0.35,
+            Self::Full => 1.0,
+        }
+    }
+}
+
+/// Current trauma, from 0 (still) to 1 (shaking as hard as it goes).
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ScreenShake {
+    trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+/// Trauma from a regular enemy explosion. Bigger blasts add more.
+const EXPLOSION_TRAUMA: f32 = 0.08;
+const BUILDING_DAMAGE_TRAUMA: f32 = 0.35;
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.2;
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+const MAX_SHAKE_ANGLE: f32 = 0.03;
+/// How quickly the shake wobbles, roughly in wobbles per second.
+const SHAKE_FREQUENCY: f32 = 18.0;
+
+fn add_explosion_trauma(
+    explosion_query: Query<&Explosion, Added<Explosion>>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    for explosion in &explosion_query {
+        shake.add_trauma(EXPLOSION_TRAUMA * explosion.radius / ENEMY_EXPLOSION_RADIUS);
+    }
+}
+
+fn add_building_damage_trauma(
+    mut ev_building_damaged: EventReader<BuildingDamaged>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    for _ in ev_building_damaged.read() {
+        shake.add_trauma(BUILDING_DAMAGE_TRAUMA);
+    }
+}
+
+/// Smooth-ish noise in -1..1 from a few out-of-step sine waves, so the shake
+/// wanders rather than jittering randomly every frame.
+fn shake_noise(t: f32, seed: f32) -> f32 {
+    ((t + seed).sin() + (t * 2.3 + seed * 1.7).sin() * 0.5 + (t * 4.1 + seed * 2.9).sin() * 0.25)
+        / 1.75
+}
+
+/// Uses real time, so the camera keeps settling during hit-stop.
+fn apply_screen_shake(
+    mut shake: ResMut<ScreenShake>,
+    shake_level: Res<ShakeLevel>,
+    time: Res<Time<Real>>,
+    mut camera: Single<&mut Transform, With<Camera2d>>,
+) {
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_secs()).max(0.0);
+    let amount = shake.trauma.powi(2) * shake_level.multiplier();
+    let t = time.elapsed_secs() * SHAKE_FREQUENCY;
+    camera.translation.x = MAX_SHAKE_OFFSET * amount * shake_noise(t, 0.0);
+    camera.translation.y = MAX_SHAKE_OFFSET * amount * shake_noise(t, 10.0);
+    camera.rotation = Quat::from_rotation_z(MAX_SHAKE_ANGLE * amount * shake_noise(t, 20.0));
+}
+
+/// Links in the chain going off right now, and when the current hit-stop
+/// (if any) ends.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+struct HitStop {
+    chain_links: u32,
+    /// In real time, since gameplay time is what's stopped.
+    until: Option<f32>,
+}
+
+/// Every this many links in one chain freezes the game for a moment.
+const HIT_STOP_EVERY: u32 = 10;
+const HIT_STOP_SECS: f32 = 0.06;
+/// Later freezes in the same chain last a little longer...
+const HIT_STOP_SECS_PER_REPEAT: f32 = 0.02;
+/// ...up to this long.
+const MAX_HIT_STOP_SECS: f32 = 0.15;
+
+fn count_chain_links(
+    _: Trigger<ScoreEvent>,
+    mut hit_stop: ResMut<HitStop>,
+    session: Option<Res<NetSession>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    hit_stop.chain_links += 1;
+    // freezing one side of an online game would throw off the lockstep
+    if session.is_some() || !hit_stop.chain_links.is_multiple_of(HIT_STOP_EVERY) {
+        return;
+    }
+    let repeats = hit_stop.chain_links / HIT_STOP_EVERY - 1;
+    let secs = (HIT_STOP_SECS + repeats as f32 * HIT_STOP_SECS_PER_REPEAT).min(MAX_HIT_STOP_SECS);
+    hit_stop.until = Some(real_time.elapsed_secs() + secs);
+    virtual_time.pause();
+}
+
+/// Once everything has finished exploding, the next link starts a new chain.
+fn end_chain(explosion_query: Query<(), With<Explosion>>, mut hit_stop: ResMut<HitStop>) {
+    if explosion_query.is_empty() {
+        hit_stop.chain_links = 0;
+    }
+}
+
+fn end_hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    if hit_stop
+        .until
+        .is_some_and(|until| real_time.elapsed_secs() >= until)
+    {
+        hit_stop.until = None;
+        virtual_time.unpause();
+    }
+}
+
+fn reset_camera_effects(
+    mut shake: ResMut<ScreenShake>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut camera: Single<&mut Transform, With<Camera2d>>,
+) {
+    shake.trauma = 0.0;
+    if hit_stop.until.take().is_some() {
+        virtual_time.unpause();
+    }
+    hit_stop.chain_links = 0;
+    **camera = Transform::default();
+}
@@ -0,0 +1,143 @@
+//! The daily challenge. Today's date picks the run seed and a handful of rule
+//! modifiers, so everyone playing on the same day gets the same run.
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::{floating_text::NewText, mode::GameMode, rng::RunSeed};
+use crate::screens::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RuleModifiers>();
+    app.init_resource::<RuleModifiers>();
+    app.register_type::<DailyDay>();
+    app.init_resource::<DailyDay>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), apply_rule_modifiers);
+}
+
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Days since the Unix epoch (UTC).
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs() / SECS_PER_DAY)
+}
+
+/// The day the current run's seed came from. Picked when the run starts,
+/// so a daily run that goes past midnight still counts towards the day it
+/// was started on.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub struct DailyDay(pub u64);
+
+/// Spreads consecutive days out so their seeds don't look alike.
+pub fn seed_for_day(day: u64) -> u64 {
+    day.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ 0xda11_c4a1_1e46_e5ee
+}
+
+/// Mixed into the seed so picking modifiers doesn't eat into the layout stream.
+const MODIFIERS_STREAM: u64 = 0x0d1f_1e45_0d1f_1e45;
+const MODIFIER_CHANCE: f64 = 0.4;
+
+/// Tweaks to the usual rules for the current run. Only the daily challenge sets any.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RuleModifiers {
+    pub double_speed_enemies: bool,
+    pub tiny_explosions: bool,
+    pub no_combos: bool,
+}
+
+impl RuleModifiers {
+    /// Rolls a set of modifiers from a daily seed, always picking at least one.
+    fn from_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed ^ MODIFIERS_STREAM);
+        let mut modifiers = Self {
+            double_speed_enemies: rng.gen_bool(MODIFIER_CHANCE),
+            tiny_explosions: rng.gen_bool(MODIFIER_CHANCE),
+            no_combos: rng.gen_bool(MODIFIER_CHANCE),
+        };
+        if modifiers == Self::default() {
+            match rng.gen_range(0..3) {
+                0 => modifiers.double_speed_enemies = true,
+                1 => modifiers.tiny_explosions = true,
+                _ => modifiers.no_combos = true,
+            }
+        }
+        modifiers
+    }
+
+    pub fn enemy_speed_multiplier(&self) -> f32 {
+        if self.double_speed_enemies { 2.0 } else { 1.0 }
+    }
+
+    pub fn explosion_radius_multiplier(&self) -> f32 {
+        if self.tiny_explosions { 0.5 } else { 1.0 }
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        [
+            (self.double_speed_enemies, "double speed"),
+            (self.tiny_explosions, "tiny booms"),
+            (self.no_combos, "no combos"),
+        ]
+        .into_iter()
+        .filter_map(|(active, name)| active.then_some(name))
+        .collect()
+    }
+}
+
+fn apply_rule_modifiers(
+    game_mode: Res<GameMode>,
+    run_seed: Res<RunSeed>,
+    mut modifiers: ResMut<RuleModifiers>,
+    mut ev_new_text: EventWriter<NewText>,
+) {
+    *modifiers = match *game_mode {
+        GameMode::Daily => RuleModifiers::from_seed(run_seed.0),
+        GameMode::Endless
+        | GameMode::TimeAttack
+        | GameMode::Survival
+        | GameMode::Puzzle
+        | GameMode::Versus => RuleModifiers::default(),
+    };
+    if *game_mode == GameMode::Daily {
+        ev_new_text.write(NewText(
+            format!("daily: {}", modifiers.names().join(", ")),
+            0.0,
+            100.0,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_day_gets_its_own_seed() {
+        assert_eq!(seed_for_day(20_000), seed_for_day(20_000));
+        assert_ne!(seed_for_day(20_000), seed_for_day(20_001));
+    }
+
+    #[test]
+    fn same_seed_same_modifiers() {
+        let seed = seed_for_day(20_000);
+        assert_eq!(
+            RuleModifiers::from_seed(seed),
+            RuleModifiers::from_seed(seed)
+        );
+    }
+
+    #[test]
+    fn every_day_has_a_modifier() {
+        for day in 0..1000 {
+            let modifiers = RuleModifiers::from_seed(seed_for_day(day));
+            assert_ne!(modifiers, RuleModifiers::default(), "day {day}");
+            assert!(!modifiers.names().is_empty());
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Optional adaptive difficulty.
+//!
+//! Tracks how the player is doing (recent building damage, shot accuracy and
+//! chain sizes) and nudges the [`EnemyController`](super::enemies::EnemyController)
+//! spawn rate and enemy speed up or down within fixed limits.
+
+use bevy::prelude::*;
+
+use super::{
+    buildings::BuildingDamaged, explosions::ShotResolved, player::MAX_PLAYERS,
+    score::ScoreController,
+};
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<AdaptiveDifficulty>();
+    app.init_resource::<AdaptiveDifficulty>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_adaptive_difficulty);
+
+    app.add_systems(
+        FixedUpdate,
+        (
+            track_building_damage,
+            track_shot_accuracy,
+            track_chain_sizes,
+            update_adjustment,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Accuracy we consider "on par" (fraction of shots that start a chain).
+const TARGET_ACCURACY: f32 = 0.5;
+/// Chain size we consider "on par".
+const TARGET_CHAIN_SIZE: f32 = 3.0;
+/// How quickly accuracy / chain averages follow new samples (0..1).
+const SAMPLE_SMOOTHING: f32 = 0.2;
+/// Seconds it takes recent building damage to fall off to ~37%.
+const DAMAGE_MEMORY_SECS: f32 = 15.0;
+/// How much each remembered building hit counts against the player.
+const DAMAGE_WEIGHT: f32 = 0.4;
+/// Max change in adjustment per second, so difficulty drifts instead of jumping.
+const ADJUSTMENT_RATE: f32 = 0.1;
+const MIN_ADJUSTMENT: f32 = -1.0;
+const MAX_ADJUSTMENT: f32 = 1.0;
+/// Enemy speed is scaled by `1.0 + adjustment * SPEED_RANGE`.
+const SPEED_RANGE: f32 = 0.35;
+/// Time between enemy spawns is scaled by `1.0 - adjustment * SPAWN_INTERVAL_RANGE`.
+const SPAWN_INTERVAL_RANGE: f32 = 0.6;
+
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct AdaptiveDifficulty {
+    /// Toggled from the settings menu, off by default.
+    pub enabled: bool,
+    /// Current adjustment, between [`MIN_ADJUSTMENT`] (easier) and [`MAX_ADJUSTMENT`] (harder).
+    pub adjustment: f32,
+    accuracy: f32,
+    chain_size: f32,
+    recent_damage: f32,
+    /// Each player's combo last tick, so a chain ending for one player isn't
+    /// hidden by the other's.
+    last_combos: [u32; MAX_PLAYERS],
+}
+
+impl Default for AdaptiveDifficulty {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            adjustment: 0.0,
+            accuracy: TARGET_ACCURACY,
+            chain_size: TARGET_CHAIN_SIZE,
+            recent_damage: 0.0,
+            last_combos: [0; MAX_PLAYERS],
+        }
+    }
+}
+
+impl AdaptiveDifficulty {
+    /// Multiplier applied to enemy speed.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.enabled {
+            1.0 + self.adjustment * SPEED_RANGE
+        } else {
+            1.0
+        }
+    }
+
+    /// Multiplier applied to the minimum time between enemy spawns.
+    pub fn spawn_interval_multiplier(&self) -> f32 {
+        if self.enabled {
+            1.0 - self.adjustment * SPAWN_INTERVAL_RANGE
+        } else {
+            1.0
+        }
+    }
+
+    /// How well the player is doing right now, between -1.0 (struggling) and 1.0 (coasting).
+    fn performance(&self) -> f32 {
+        let accuracy_term = (self.accuracy - TARGET_ACCURACY) * 2.0;
+        let chain_term = (self.chain_size - TARGET_CHAIN_SIZE) / TARGET_CHAIN_SIZE;
+        let damage_term = self.recent_damage * DAMAGE_WEIGHT;
+        (accuracy_term + chain_term - damage_term).clamp(MIN_ADJUSTMENT, MAX_ADJUSTMENT)
+    }
+}
+
+fn reset_adaptive_difficulty(mut difficulty: ResMut<AdaptiveDifficulty>) {
+    *difficulty = AdaptiveDifficulty {
+        enabled: difficulty.enabled,
+        ..default()
+    };
+}
+
+fn track_building_damage(
+    mut difficulty: ResMut<AdaptiveDifficulty>,
+    mut ev_building_damaged: EventReader<BuildingDamaged>,
+    time: Res<Time>,
+) {
+    difficulty.recent_damage *= (-time.delta_secs() / DAMAGE_MEMORY_SECS).exp();
+    for _ in ev_building_damaged.read() {
+        difficulty.recent_damage += 1.0;
+    }
+}
+
+fn track_shot_accuracy(
+    mut difficulty: ResMut<AdaptiveDifficulty>,
+    mut ev_shot_resolved: EventReader<ShotResolved>,
+) {
+    for ev in ev_shot_resolved.read() {
+        let sample = if ev.hit { 1.0 } else { 0.0 };
+        difficulty.accuracy = difficulty.accuracy.lerp(sample, SAMPLE_SMOOTHING);
+    }
+}
+
+fn track_chain_sizes(
+    mut difficulty: ResMut<AdaptiveDifficulty>,
+    score_controller_query: Query<&ScoreController>,
+) {
+    for controller in score_controller_query.iter() {
+        for (player, combo) in controller.combos.iter().enumerate() {
+            // a drop in combo means that player's previous chain just finished
+            let last_combo = difficulty.last_combos[player];
+            if combo.count < last_combo {
+                difficulty.chain_size = difficulty
+                    .chain_size
+                    .lerp(last_combo as f32, SAMPLE_SMOOTHING);
+            }
+            difficulty.last_combos[player] = combo.count;
+        }
+    }
+}
+
+fn update_adjustment(mut difficulty: ResMut<AdaptiveDifficulty>, time: Res<Time>) {
+    if !difficulty.enabled {
+        return;
+    }
+    let target = difficulty.performance();
+    let max_step = ADJUSTMENT_RATE * time.delta_secs();
+    let step = (target - difficulty.adjustment).clamp(-max_step, max_step);
+    difficulty.adjustment = (difficulty.adjustment + step).clamp(MIN_ADJUSTMENT, MAX_ADJUSTMENT);
+}
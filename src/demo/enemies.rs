@@ -2,19 +2,27 @@ use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
+use rand::Rng;
 
 use super::{
+    daily::RuleModifiers,
+    difficulty::AdaptiveDifficulty,
     explosions::{ExplosionAssets, ExplosionController},
+    field::{PlayArea, PlayField},
     floating_text::NewText,
+    mode::{GameMode, RunClock},
     movement::MovementController,
+    rng::GameRng,
+    simulation::Interpolated,
 };
 use crate::{
-    AppSystems, PausableSystems, asset_tracking::LoadResource, demo::movement::MAX_X,
+    AppSystems, PausableSystems, asset_tracking::LoadResource, background::TimeOfDay,
     screens::Screen,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Enemy>();
+    app.register_type::<GarbageEnemy>();
 
     app.register_type::<EnemyAssets>();
     app.load_resource::<EnemyAssets>();
@@ -22,7 +30,7 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<EnemyController>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (update_enemy_movement_intent)
             .chain()
             .run_if(resource_exists::<ExplosionAssets>)
@@ -32,7 +40,7 @@ pub(super) fn plugin(app: &mut App) {
     );
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (update_enemy_controller)
             .chain()
             .run_if(resource_exists::<ExplosionAssets>)
@@ -44,7 +52,7 @@ pub(super) fn plugin(app: &mut App) {
 
 #[derive(Component, Debug, Clone, PartialEq, Default, Reflect)]
 #[reflect(Component)]
-struct Enemy {
+pub struct Enemy {
     pub speed: f32,
     pub last_movement_time: f32,
     pub time_between_movements: f32,
@@ -52,31 +60,66 @@ struct Enemy {
     pub target_locs: Vec<Vec2>,
 }
 
-const OFFSCREEN_ABOVE: f32 = 500.0;
-const MIN_ENEMY_Y_BELOW: f32 = -125.0;
-
-pub fn create_target_locs() -> Vec<Vec2> {
+pub fn create_target_locs(field: &PlayField, area: &PlayArea, rng: &mut impl Rng) -> Vec<Vec2> {
     let mut locs = vec![];
-    // create an x val that's somewhere in between max and min x vals
-    let x_val = rand::random::<f32>() * MAX_X * 2.0 - MAX_X;
+    // create an x val that's somewhere in between the field's max and min x vals
+    let x_val = field.random_x(rng);
     // start at the top of the screen (out of view)
-    locs.push(Vec2::new(x_val, OFFSCREEN_ABOVE));
-    let x_val_2 = rand::random::<f32>() * MAX_X * 2.0 - MAX_X;
+    locs.push(Vec2::new(x_val, area.spawn_y));
+    let x_val_2 = field.random_x(rng);
     locs.push(Vec2 {
         x: x_val_2,
-        y: (OFFSCREEN_ABOVE + MIN_ENEMY_Y_BELOW) / 2.0,
+        y: (area.spawn_y + area.enemy_floor_y) / 2.0,
     });
     // go down to the bottom of the screen (in view at about player level)
-    locs.push(Vec2::new(x_val, MIN_ENEMY_Y_BELOW));
+    locs.push(Vec2::new(x_val, area.enemy_floor_y));
     locs
 }
 
 // todo:: pass initial transfrom and use to spawn shot
-pub fn enemy(speed: f32, enemy_assets: &EnemyAssets) -> impl Bundle {
-    let target_locs = create_target_locs();
-    let initial_location = target_locs[0];
+pub fn enemy(
+    speed: f32,
+    field: PlayField,
+    area: &PlayArea,
+    enemy_assets: &EnemyAssets,
+    rng: &mut impl Rng,
+) -> impl Bundle {
     (
         Name::new("Enemy"),
+        Sprite {
+            image: enemy_assets.texture.clone(),
+            ..default()
+        },
+        moving_enemy(speed, field, area, rng),
+    )
+}
+
+/// An extra enemy sent over by the other player in versus. Doesn't count
+/// towards the field's usual enemy limit.
+pub fn garbage_enemy(
+    speed: f32,
+    field: PlayField,
+    area: &PlayArea,
+    enemy_assets: &EnemyAssets,
+    rng: &mut impl Rng,
+) -> impl Bundle {
+    (
+        Name::new("Garbage Enemy"),
+        GarbageEnemy,
+        Sprite {
+            image: enemy_assets.texture.clone(),
+            color: GARBAGE_ENEMY_COLOR,
+            ..default()
+        },
+        moving_enemy(speed, field, area, rng),
+    )
+}
+
+/// Everything an enemy that heads for the ground needs, apart from how it looks.
+fn moving_enemy(speed: f32, field: PlayField, area: &PlayArea, rng: &mut impl Rng) -> impl Bundle {
+    let target_locs = create_target_locs(&field, area, rng);
+    let initial_location = target_locs[0];
+    (
         Enemy {
             speed,
             last_movement_time: 0.0,
@@ -85,12 +128,48 @@ pub fn enemy(speed: f32, enemy_assets: &EnemyAssets) -> impl Bundle {
             target_locs,
         },
         Transform::from_translation(initial_location.extend(0.0)).with_scale(Vec3::splat(4.0)),
+        MovementController::default(),
+        Interpolated::default(),
+        ExplosionController::new(
+            false,
+            ENEMY_EXPLOSION_RADIUS,
+            ENEMY_HITBOX_RADIUS,
+            ENEMY_EXPLOSION_LIFE_SPAN,
+        ),
+        field,
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+const GARBAGE_ENEMY_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct GarbageEnemy;
+
+pub const ENEMY_EXPLOSION_RADIUS: f32 = 32.0;
+const ENEMY_HITBOX_RADIUS: f32 = 32.0;
+const ENEMY_EXPLOSION_LIFE_SPAN: f32 = 0.5;
+
+/// An enemy that sits still until something sets it off. Used for puzzle layouts.
+pub fn stationary_enemy(
+    position: Vec2,
+    explosion_radius: f32,
+    enemy_assets: &EnemyAssets,
+) -> impl Bundle {
+    (
+        Name::new("Stationary Enemy"),
+        Transform::from_translation(position.extend(0.0)).with_scale(Vec3::splat(4.0)),
         Sprite {
             image: enemy_assets.texture.clone(),
             ..default()
         },
-        MovementController::default(),
-        ExplosionController::new(false, 32.0, 32.0, 0.5),
+        ExplosionController::new(
+            false,
+            explosion_radius,
+            ENEMY_HITBOX_RADIUS,
+            ENEMY_EXPLOSION_LIFE_SPAN,
+        ),
         StateScoped(Screen::Gameplay),
     )
 }
@@ -127,6 +206,7 @@ fn update_enemy_movement_intent(
         &mut Enemy,
         &Transform,
     )>,
+    play_area: Res<PlayArea>,
 ) {
     for (mut movement_controller, mut explosion_controller, mut enemy, transform) in
         query.iter_mut()
@@ -145,7 +225,7 @@ fn update_enemy_movement_intent(
         let direction = (target - transform.translation.truncate()).normalize();
         movement_controller.intent = direction;
         // modify speed based on distance to min y val
-        let distance_to_min_y = transform.translation.y - MIN_ENEMY_Y_BELOW;
+        let distance_to_min_y = transform.translation.y - play_area.enemy_floor_y;
         // make enemy explode if it got real close
         if distance_to_min_y < EXPLOSION_DISTANCE {
             explosion_controller.should_explode = true;
@@ -169,6 +249,13 @@ pub struct EnemyController {
     pub last_enemy_spawn_time: f32,
 }
 
+impl EnemyController {
+    /// Seconds until the next level, under the given mode's rules.
+    pub fn time_to_next_level(&self, game_mode: GameMode) -> f32 {
+        (SpawnRules::for_mode(game_mode).level_time - self.game_time).max(0.0)
+    }
+}
+
 impl Default for EnemyController {
     fn default() -> Self {
         Self {
@@ -181,34 +268,88 @@ impl Default for EnemyController {
     }
 }
 
-const LEVEL_TIME: f32 = 10.0;
-const NUM_ENEMIES_INCREMENT_PER_LEVEL: usize = 5;
-const ENEMY_SPEED_INCREMENT_PER_LEVEL: f32 = 20.0;
 const MIN_ENEMY_SPAWN_INTERVAL: f32 = 0.2;
 
+/// How the enemy waves ramp up over a run.
+struct SpawnRules {
+    level_time: f32,
+    num_enemies_increment_per_level: usize,
+    enemy_speed_increment_per_level: f32,
+}
+
+impl SpawnRules {
+    fn for_mode(game_mode: GameMode) -> Self {
+        match game_mode {
+            // puzzles don't spawn waves, but fall back to the usual ramp
+            GameMode::Endless | GameMode::Daily | GameMode::Puzzle | GameMode::Versus => Self {
+                level_time: 10.0,
+                num_enemies_increment_per_level: 5,
+                enemy_speed_increment_per_level: 20.0,
+            },
+            // short run, so ramp up quicker to keep the chains coming
+            GameMode::TimeAttack => Self {
+                level_time: 8.0,
+                num_enemies_increment_per_level: 6,
+                enemy_speed_increment_per_level: 15.0,
+            },
+            // only one building to protect, so fewer enemies but they still get faster
+            GameMode::Survival => Self {
+                level_time: 15.0,
+                num_enemies_increment_per_level: 3,
+                enemy_speed_increment_per_level: 20.0,
+            },
+        }
+    }
+}
+
 fn update_enemy_controller(
-    enemy_query: Query<&Enemy>,
-    mut controller_query: Query<&mut EnemyController>,
+    enemy_query: Query<&PlayField, (With<Enemy>, Without<GarbageEnemy>)>,
+    mut controller_query: Query<(&mut EnemyController, &PlayField)>,
     enemy_assets: Res<EnemyAssets>,
     mut commands: Commands,
     time: Res<Time>,
     mut ev_new_text: EventWriter<NewText>,
+    difficulty: Res<AdaptiveDifficulty>,
+    game_mode: Res<GameMode>,
+    modifiers: Res<RuleModifiers>,
+    mut game_rng: ResMut<GameRng>,
+    run_clock: Res<RunClock>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    play_area: Res<PlayArea>,
 ) {
-    let enemy_count = enemy_query.iter().count();
-    for mut controller in controller_query.iter_mut() {
+    let rules = SpawnRules::for_mode(*game_mode);
+    for (mut controller, field) in controller_query.iter_mut() {
+        let enemy_count = enemy_query
+            .iter()
+            .filter(|enemy_field| enemy_field.index == field.index)
+            .count();
         controller.game_time += time.delta_secs();
-        if controller.game_time >= LEVEL_TIME {
+        if controller.game_time >= rules.level_time {
             controller.level += 1;
-            ev_new_text.write(NewText(format!("level {}", controller.level), 0.0, 0.0));
-            controller.max_num_enemies += NUM_ENEMIES_INCREMENT_PER_LEVEL;
-            controller.enemy_speed += ENEMY_SPEED_INCREMENT_PER_LEVEL;
+            time_of_day.reach_level(controller.level);
+            ev_new_text.write(NewText(
+                format!("level {}", controller.level),
+                field.center_x,
+                0.0,
+            ));
+            controller.max_num_enemies += rules.num_enemies_increment_per_level;
+            controller.enemy_speed += rules.enemy_speed_increment_per_level;
             controller.game_time = 0.0;
         }
-        let can_spawn_now =
-            time.elapsed_secs() - controller.last_enemy_spawn_time >= MIN_ENEMY_SPAWN_INTERVAL;
+        let spawn_interval = MIN_ENEMY_SPAWN_INTERVAL * difficulty.spawn_interval_multiplier();
+        let can_spawn_now = run_clock.0 - controller.last_enemy_spawn_time >= spawn_interval;
         if enemy_count < controller.max_num_enemies && can_spawn_now {
-            commands.spawn(enemy(controller.enemy_speed, &enemy_assets));
-            controller.last_enemy_spawn_time = time.elapsed_secs();
+            let speed = controller.enemy_speed
+                * difficulty.speed_multiplier()
+                * modifiers.enemy_speed_multiplier();
+            commands.spawn(enemy(
+                speed,
+                *field,
+                &play_area,
+                &enemy_assets,
+                &mut game_rng.enemy_paths,
+            ));
+            controller.last_enemy_spawn_time = run_clock.0;
         }
     }
 }
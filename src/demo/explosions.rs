@@ -2,24 +2,39 @@ use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
+use chain_reaction_jam::spatial::{self, Hitbox, SpatialGrid, rebuild_grid};
+use rand::Rng;
 
 use crate::{
-    AppSystems, PausableSystems, asset_tracking::LoadResource, audio::sound_effect,
-    demo::score::ScoreEvent, screens::Screen,
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::{PlaySfx, SfxCategory},
+    demo::{
+        daily::RuleModifiers,
+        enemies::ENEMY_EXPLOSION_RADIUS,
+        field::PlayArea,
+        score::{ScoreController, ScoreEvent},
+    },
+    screens::Screen,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Explosion>();
     app.register_type::<ExplosionController>();
+    app.register_type::<ShotExplosion>();
+    app.register_type::<ChainOwner>();
+    app.add_event::<ShotResolved>();
+    app.add_plugins(spatial::plugin::<ExplosionController>);
 
     app.register_type::<ExplosionAssets>();
     app.load_resource::<ExplosionAssets>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
             create_explosions,
             update_explosions,
+            rebuild_grid::<ExplosionController>,
             check_for_explosion_chain,
         )
             .chain()
@@ -27,6 +42,12 @@ pub(super) fn plugin(app: &mut App) {
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        Update,
+        play_explosion_sounds
+            .run_if(resource_exists::<ExplosionAssets>)
+            .in_set(AppSystems::Update),
+    );
 }
 
 #[derive(Component, Reflect)]
@@ -49,6 +70,12 @@ impl Default for ExplosionController {
     }
 }
 
+impl Hitbox for ExplosionController {
+    fn hitbox_radius(&self) -> f32 {
+        self.hitbox_radius
+    }
+}
+
 impl ExplosionController {
     pub fn new(
         should_explode: bool,
@@ -101,6 +128,26 @@ impl Explosion {
     }
 }
 
+/// Marks an explosion that was caused directly by a player's shot, so we can
+/// tell whether the shot started a chain or missed.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ShotExplosion {
+    pub hit: bool,
+}
+
+/// The player whose shot set this off. Passed along from explosion to
+/// explosion so the whole chain's points go to them.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct ChainOwner(pub usize);
+
+/// Sent when a shot's explosion finishes, with whether it set anything off.
+#[derive(Event)]
+pub struct ShotResolved {
+    pub hit: bool,
+}
+
 pub fn explosion(
     position: Vec2,
     radius: f32,
@@ -112,8 +159,6 @@ pub fn explosion(
     initial_transform.translation = position.extend(1.0);
     let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 11, 1, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let random_boom_index = (rand::random::<f32>() * 5.0).floor() as usize;
-    let boom_noise_handle = explosion_assets.booms[random_boom_index].clone();
     (
         Explosion::new(radius, position, lifespan),
         Sprite {
@@ -126,7 +171,6 @@ pub fn explosion(
         },
         initial_transform,
         StateScoped(Screen::Gameplay),
-        children![sound_effect(boom_noise_handle)],
     )
 }
 
@@ -161,27 +205,34 @@ impl FromWorld for ExplosionAssets {
     }
 }
 
-const MAX_Y: f32 = 250.0;
-const MIN_Y: f32 = -250.0;
-
 fn create_explosions(
     mut commands: Commands,
-    query: Query<(&Transform, &ExplosionController, Entity)>,
+    query: Query<(
+        &Transform,
+        &ExplosionController,
+        Option<&ChainOwner>,
+        Entity,
+    )>,
     explosion_assets: Res<ExplosionAssets>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    modifiers: Res<RuleModifiers>,
+    play_area: Res<PlayArea>,
 ) {
     // loop through all entities with ExplosionController component & a Transform
-    for (transform, controller, entity) in query.iter() {
-        let is_onscreen = transform.translation.y > MIN_Y && transform.translation.y < MAX_Y;
+    for (transform, controller, chain_owner, entity) in query.iter() {
+        let is_onscreen = play_area.contains_y(transform.translation.y);
         if controller.should_explode && is_onscreen {
             // create explosion
-            commands.spawn(explosion(
+            let mut explosion_entity = commands.spawn(explosion(
                 transform.translation.truncate(),
-                controller.explosion_radius,
+                controller.explosion_radius * modifiers.explosion_radius_multiplier(),
                 controller.explosion_life_span,
                 &explosion_assets,
                 &mut texture_atlas_layouts,
             ));
+            if let Some(chain_owner) = chain_owner {
+                explosion_entity.insert(*chain_owner);
+            }
             // get rid of thing that exploded
             commands.entity(entity).despawn();
         }
@@ -192,11 +243,17 @@ const NUM_FRAMES: usize = 11;
 
 fn update_explosions(
     mut commands: Commands,
-    mut query: Query<(&mut Sprite, &mut Explosion, Entity)>,
+    mut query: Query<(&mut Sprite, &mut Explosion, Option<&ShotExplosion>, Entity)>,
     time: Res<Time>,
+    mut ev_shot_resolved: EventWriter<ShotResolved>,
 ) {
-    for (mut sprite, mut explosion, entity) in query.iter_mut() {
+    for (mut sprite, mut explosion, shot_explosion, entity) in query.iter_mut() {
         if explosion.explosion_life_span_remaining <= 0.0 {
+            if let Some(shot_explosion) = shot_explosion {
+                ev_shot_resolved.write(ShotResolved {
+                    hit: shot_explosion.hit,
+                });
+            }
             commands.entity(entity).despawn();
         } else {
             explosion.explosion_life_span_remaining -= time.delta_secs();
@@ -210,23 +267,70 @@ fn update_explosions(
 }
 
 fn check_for_explosion_chain(
-    explosion_query: Query<(&Transform, &Explosion)>,
-    mut can_explode_query: Query<(&Transform, &mut ExplosionController)>,
+    mut explosion_query: Query<(
+        &Transform,
+        &Explosion,
+        Option<&mut ShotExplosion>,
+        Option<&ChainOwner>,
+    )>,
+    mut can_explode_query: Query<(&mut ExplosionController, &Transform)>,
+    grid: Res<SpatialGrid<ExplosionController>>,
     mut commands: Commands,
 ) {
-    for (transform, explosion) in &explosion_query {
-        for (potential_explosion_transform, mut potential_explosion_controller) in
-            &mut can_explode_query
-        {
+    for (transform, explosion, mut shot_explosion, chain_owner) in &mut explosion_query {
+        for entity in grid.query(transform.translation.truncate(), explosion.radius) {
+            let Ok((mut potential_explosion_controller, target_transform)) =
+                can_explode_query.get_mut(entity)
+            else {
+                continue;
+            };
             if !potential_explosion_controller.should_explode {
-                let distance = transform
-                    .translation
-                    .distance(potential_explosion_transform.translation);
-                if distance <= explosion.radius + potential_explosion_controller.hitbox_radius {
-                    potential_explosion_controller.should_explode = true;
-                    commands.trigger(ScoreEvent { score: 1 });
+                potential_explosion_controller.should_explode = true;
+                if let Some(shot_explosion) = shot_explosion.as_mut() {
+                    shot_explosion.hit = true;
+                }
+                if let Some(chain_owner) = chain_owner {
+                    commands.entity(entity).insert(*chain_owner);
                 }
+                commands.trigger(ScoreEvent {
+                    score: 1,
+                    player: chain_owner.map(|chain_owner| chain_owner.0),
+                    position: target_transform.translation.truncate(),
+                });
             }
         }
     }
 }
+
+/// Each link in a chain booms this much higher than the last...
+const CHAIN_PITCH_STEP: f32 = 0.03;
+/// ...up to this much faster than normal.
+const MAX_CHAIN_PITCH: f32 = 1.6;
+
+/// Booms rise in pitch the deeper the chain gets, come from where they go off,
+/// and bigger blasts win out when there are too many to play at once.
+fn play_explosion_sounds(
+    explosion_query: Query<&Explosion, Added<Explosion>>,
+    score_controller_query: Query<&ScoreController>,
+    explosion_assets: Res<ExplosionAssets>,
+    mut ev_play_sfx: EventWriter<PlaySfx>,
+) {
+    let combo = score_controller_query
+        .iter()
+        .map(ScoreController::combo)
+        .max()
+        .unwrap_or_default();
+    let pitch = (1.0 + combo.saturating_sub(1) as f32 * CHAIN_PITCH_STEP).min(MAX_CHAIN_PITCH);
+    for explosion in &explosion_query {
+        let boom_index = rand::thread_rng().gen_range(0..explosion_assets.booms.len());
+        ev_play_sfx.write(
+            PlaySfx::new(
+                explosion_assets.booms[boom_index].clone(),
+                SfxCategory::Explosion,
+            )
+            .with_priority(explosion.radius / ENEMY_EXPLOSION_RADIUS)
+            .with_pitch(pitch)
+            .at(explosion.center),
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Play fields: the strip of screen a player defends. Most modes have one
+//! field covering the whole screen, versus splits it in two so each player
+//! has their own side.
+//!
+//! Everything is laid out in a fixed [`PlayArea`], and the camera scales that
+//! to fit the window, so the game plays the same at any window size.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::mode::GameMode;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PlayField>();
+    app.register_type::<PlayArea>();
+    app.init_resource::<PlayArea>();
+}
+
+/// The space the game is laid out in, in world units. The camera always shows
+/// at least `view_size` of it, however big the window is.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct PlayArea {
+    /// What the camera always fits on screen. Wider or taller windows see a
+    /// bit more backdrop around the edges.
+    pub view_size: Vec2,
+    /// The frame drawn around the play field.
+    pub frame_size: Vec2,
+    /// How far either side of the middle cannons can go.
+    pub half_width: f32,
+    /// Things only explode between these heights, so nothing goes off out of
+    /// view.
+    pub min_y: f32,
+    pub max_y: f32,
+    /// Where enemies start, out of view above the field.
+    pub spawn_y: f32,
+    /// Where the cannons sit.
+    pub player_y: f32,
+    /// Where the middle of each building sits.
+    pub building_y: f32,
+    /// How low enemies come, just above the buildings. They blow up when
+    /// they get there.
+    pub enemy_floor_y: f32,
+}
+
+impl Default for PlayArea {
+    fn default() -> Self {
+        Self {
+            view_size: Vec2::new(800.0, 640.0),
+            frame_size: Vec2::new(760.0, 600.0),
+            half_width: 330.0,
+            min_y: -250.0,
+            max_y: 250.0,
+            spawn_y: 500.0,
+            player_y: -220.0,
+            building_y: -160.0,
+            enemy_floor_y: -125.0,
+        }
+    }
+}
+
+impl PlayArea {
+    pub fn contains_y(&self, y: f32) -> bool {
+        y > self.min_y && y < self.max_y
+    }
+}
+
+/// How far apart the centres of the two versus fields are.
+const VERSUS_FIELD_SPACING: f32 = 400.0;
+/// Leaves a gap either side of the divider so cannons don't overlap.
+const VERSUS_FIELD_HALF_WIDTH: f32 = 160.0;
+
+/// Which field an entity belongs to, and where that field is.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct PlayField {
+    /// Also the index of the player defending it in versus.
+    pub index: usize,
+    pub center_x: f32,
+    pub half_width: f32,
+}
+
+impl PlayField {
+    /// A single field covering the whole play area.
+    pub fn whole(area: &PlayArea) -> Self {
+        Self {
+            index: 0,
+            center_x: 0.0,
+            half_width: area.half_width,
+        }
+    }
+
+    /// The fields a run of the given mode is played on.
+    pub fn for_mode(game_mode: GameMode, area: &PlayArea) -> Vec<Self> {
+        match game_mode {
+            GameMode::Versus => (0..2)
+                .map(|index| Self {
+                    index,
+                    center_x: (index as f32 - 0.5) * VERSUS_FIELD_SPACING,
+                    half_width: VERSUS_FIELD_HALF_WIDTH,
+                })
+                .collect(),
+            GameMode::Endless
+            | GameMode::TimeAttack
+            | GameMode::Survival
+            | GameMode::Daily
+            | GameMode::Puzzle => vec![Self::whole(area)],
+        }
+    }
+
+    pub fn min_x(&self) -> f32 {
+        self.center_x - self.half_width
+    }
+
+    pub fn max_x(&self) -> f32 {
+        self.center_x + self.half_width
+    }
+
+    pub fn clamp_x(&self, x: f32) -> f32 {
+        x.clamp(self.min_x(), self.max_x())
+    }
+
+    pub fn random_x(&self, rng: &mut impl Rng) -> f32 {
+        self.min_x() + rng.r#gen::<f32>() * self.half_width * 2.0
+    }
+}
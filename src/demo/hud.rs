@@ -0,0 +1,269 @@
+//! The gameplay HUD along the bottom of the screen, between the score and
+//! combo boxes: the current level, how each building is holding up, and each
+//! cannon's reload.
+
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+use super::{
+    buildings::{BUILDING_MAX_HEALTH, Building, Destroyed},
+    enemies::EnemyController,
+    mode::{GameMode, RunClock},
+    player::{Player, player_color},
+    score::ScoreUIAssets,
+    shooting::ShootingController,
+};
+use crate::{AppSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelVal>();
+    app.register_type::<NextLevelVal>();
+    app.register_type::<BuildingPips>();
+    app.register_type::<ReloadBar>();
+
+    app.add_systems(
+        Update,
+        (update_level_ui, update_building_pips, update_reload_bars)
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// The framed box every piece of the HUD sits in, with its contents stacked
+/// in a column. `node` places and pads it.
+pub(super) fn hud_frame(score_ui_assets: &ScoreUIAssets, node: Node) -> impl Bundle {
+    let slicer = TextureSlicer {
+        border: BorderRect::all(5.0),
+        center_scale_mode: SliceScaleMode::Stretch,
+        sides_scale_mode: SliceScaleMode::Stretch,
+        max_corner_scale: 1.0,
+    };
+    (
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            ..node
+        },
+        ImageNode {
+            image: score_ui_assets.frame.clone(),
+            image_mode: NodeImageMode::Sliced(slicer),
+            ..default()
+        },
+    )
+}
+
+/// The boxes along the bottom, which line up on their own.
+fn bottom_frame(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    hud_frame(
+        score_ui_assets,
+        Node {
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+    )
+}
+
+/// The HUD boxes for a run. Puzzles have no levels or buildings, so they only
+/// get the reload box. `tinted` colours the reload bars to match co-op
+/// cannons.
+pub fn hud_ui(
+    score_ui_assets: &ScoreUIAssets,
+    game_mode: GameMode,
+    player_count: usize,
+    tinted: bool,
+) -> impl Bundle {
+    let has_waves = game_mode != GameMode::Puzzle;
+    let level_ui = has_waves.then(|| level_ui(score_ui_assets));
+    let building_health_ui = has_waves.then(|| building_health_ui(score_ui_assets));
+    (
+        Name::new("HUD"),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::End,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        Children::spawn((
+            SpawnIter(level_ui.into_iter()),
+            SpawnIter(building_health_ui.into_iter()),
+            Spawn(reload_ui(score_ui_assets, player_count, tinted)),
+        )),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn level_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    (
+        bottom_frame(score_ui_assets),
+        children![
+            (Text::new("level"), TextFont::from_font_size(18.0),),
+            (LevelVal, Text::new("1"), TextFont::from_font_size(24.0),),
+            (NextLevelVal, Text::new(""), TextFont::from_font_size(14.0),)
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct LevelVal;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct NextLevelVal;
+
+/// Every field levels up together, so the first controller speaks for all of
+/// them.
+fn update_level_ui(
+    controller_query: Query<&EnemyController>,
+    game_mode: Res<GameMode>,
+    mut level_text: Query<&mut Text, (With<LevelVal>, Without<NextLevelVal>)>,
+    mut next_level_text: Query<&mut Text, (With<NextLevelVal>, Without<LevelVal>)>,
+) {
+    let Some(controller) = controller_query.iter().next() else {
+        return;
+    };
+    for mut text in level_text.iter_mut() {
+        text.0 = format!("{}", controller.level);
+    }
+    let next_level = controller.time_to_next_level(*game_mode).ceil() as u32;
+    for mut text in next_level_text.iter_mut() {
+        text.0 = format!("next in {next_level}s");
+    }
+}
+
+const PIP_SIZE: f32 = 12.0;
+
+fn building_health_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    (
+        bottom_frame(score_ui_assets),
+        children![
+            (Text::new("buildings"), TextFont::from_font_size(18.0),),
+            (
+                BuildingPips,
+                Node {
+                    column_gap: Val::Px(6.0),
+                    min_height: Val::Px(PIP_SIZE),
+                    ..default()
+                },
+            )
+        ],
+    )
+}
+
+/// Holds one pip per building, left to right in the same order as the
+/// buildings.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BuildingPips;
+
+fn pip_color(health: i32) -> Color {
+    match health {
+        h if h >= BUILDING_MAX_HEALTH => Color::srgb(0.4, 0.85, 0.4),
+        2 => Color::srgb(0.95, 0.8, 0.3),
+        1 => Color::srgb(0.9, 0.3, 0.25),
+        _ => Color::srgb(0.25, 0.22, 0.22),
+    }
+}
+
+/// The pips get rebuilt whenever there's a different number of buildings,
+/// so they don't depend on the buildings being spawned before the HUD.
+fn update_building_pips(
+    mut commands: Commands,
+    building_query: Query<(&Building, &Transform, Has<Destroyed>)>,
+    pips: Single<(Entity, Option<&Children>), With<BuildingPips>>,
+    mut pip_query: Query<&mut BackgroundColor>,
+) {
+    let mut buildings: Vec<_> = building_query.iter().collect();
+    buildings.sort_by(|(_, a, _), (_, b, _)| a.translation.x.total_cmp(&b.translation.x));
+    let colors = buildings
+        .iter()
+        .map(|(building, _, destroyed)| pip_color(if *destroyed { 0 } else { building.health }));
+
+    let (row, children) = *pips;
+    let pip_count = children.map_or(0, |children| children.len());
+    if pip_count != buildings.len() {
+        commands.entity(row).despawn_related::<Children>();
+        for color in colors {
+            commands.spawn((
+                Name::new("Building Pip"),
+                Node {
+                    width: Val::Px(PIP_SIZE),
+                    height: Val::Px(PIP_SIZE),
+                    ..default()
+                },
+                BackgroundColor(color),
+                ChildOf(row),
+            ));
+        }
+        return;
+    }
+    for (&pip, color) in children.into_iter().flatten().zip(colors) {
+        if let Ok(mut background) = pip_query.get_mut(pip) {
+            background.0 = color;
+        }
+    }
+}
+
+const RELOAD_BAR_WIDTH: f32 = 60.0;
+const RELOAD_BAR_HEIGHT: f32 = 6.0;
+const RELOAD_BAR_TRACK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+
+fn reload_ui(score_ui_assets: &ScoreUIAssets, player_count: usize, tinted: bool) -> impl Bundle {
+    (
+        bottom_frame(score_ui_assets),
+        Children::spawn((
+            Spawn((Text::new("reload"), TextFont::from_font_size(18.0))),
+            SpawnIter((0..player_count).map(move |index| {
+                let color = if tinted {
+                    player_color(index)
+                } else {
+                    Color::WHITE
+                };
+                (
+                    Name::new("Reload Track"),
+                    Node {
+                        width: Val::Px(RELOAD_BAR_WIDTH),
+                        height: Val::Px(RELOAD_BAR_HEIGHT),
+                        ..default()
+                    },
+                    BackgroundColor(RELOAD_BAR_TRACK_COLOR),
+                    children![(
+                        ReloadBar(index),
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                    )],
+                )
+            })),
+        )),
+    )
+}
+
+/// Fills up as the given player's cannon reloads.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ReloadBar(usize);
+
+fn update_reload_bars(
+    player_query: Query<(&Player, &ShootingController)>,
+    run_clock: Res<RunClock>,
+    mut bar_query: Query<(&mut Node, &ReloadBar)>,
+) {
+    for (player, controller) in &player_query {
+        let reloaded =
+            ((run_clock.0 - controller.last_shot_time) / controller.reload_time).clamp(0.0, 1.0);
+        for (mut node, bar) in bar_query.iter_mut() {
+            if bar.0 == player.index {
+                node.width = Val::Percent(100.0 * reloaded);
+            }
+        }
+    }
+}
@@ -3,72 +3,163 @@
 use bevy::prelude::*;
 
 use crate::{
-    asset_tracking::LoadResource,
-    audio::music,
-    background::{BGAssets, bg_layer_1, bg_layer_2, bg_layer_3},
+    AppSystems,
+    audio::MusicIntensity,
+    background::{BGAssets, Backdrop, backdrop, play_field_frame},
     demo::{
-        player::{PlayerAssets, player},
-        score::{self, ScoreUIAssets, combo_ui, score_ui},
+        field::{PlayArea, PlayField},
+        hud::hud_ui,
+        mode::{GameMode, time_left_ui},
+        player::{PlayerAssets, PlayerCount, player, player_hue, player_start_x},
+        rng::GameRng,
+        score::{self, ScoreController, ScoreUIAssets, combo_ui, player_score_ui, score_ui},
     },
     screens::Screen,
 };
 
 use super::buildings::{BuildingAssets, spawn_buildings};
-use super::enemies::EnemyController;
+use super::enemies::{Enemy, EnemyController};
 use super::reticle::{ReticleAssets, reticle};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<LevelAssets>();
-    app.load_resource::<LevelAssets>();
-}
-
-#[derive(Resource, Asset, Clone, Reflect)]
-#[reflect(Resource)]
-pub struct LevelAssets {
-    #[dependency]
-    music: Handle<AudioSource>,
-}
-
-impl FromWorld for LevelAssets {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
-        Self {
-            music: assets.load("audio/music/track_2.mp3"),
-        }
-    }
+    app.add_systems(
+        Update,
+        update_music_intensity
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_music_intensity);
 }
 
 /// A system that spawns the main level.
 pub fn spawn_level(
     mut commands: Commands,
-    level_assets: Res<LevelAssets>,
     player_assets: Res<PlayerAssets>,
     reticle_assets: Res<ReticleAssets>,
     bg_assets: Res<BGAssets>,
     score_ui_assets: Res<ScoreUIAssets>,
     building_assets: Res<BuildingAssets>,
+    game_mode: Res<GameMode>,
+    player_count: Res<PlayerCount>,
+    mut game_rng: ResMut<GameRng>,
+    play_area: Res<PlayArea>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    commands.spawn((
-        Name::new("Level"),
-        Transform::default(),
-        Visibility::default(),
-        StateScoped(Screen::Gameplay),
-        children![
-            player(500.0, &player_assets),
-            EnemyController::default(),
-            score::score_controller(),
-            (reticle(&reticle_assets), Name::new("Reticle"),),
-            bg_layer_1(&bg_assets),
-            bg_layer_2(&bg_assets),
-            bg_layer_3(&bg_assets),
-            (
-                Name::new("Gameplay Music"),
-                music(level_assets.music.clone())
-            ),
-        ],
-    ));
-    commands.spawn(score_ui(&score_ui_assets));
+    let level = commands
+        .spawn((
+            Name::new("Level"),
+            Transform::default(),
+            Visibility::default(),
+            StateScoped(Screen::Gameplay),
+            children![
+                score::score_controller(*game_mode == GameMode::Versus),
+                backdrop(Backdrop::Gameplay),
+                play_field_frame(&bg_assets, &play_area),
+            ],
+        ))
+        .id();
+    let fields = PlayField::for_mode(*game_mode, &play_area);
+    let player_count = player_count.for_mode(*game_mode);
+    // versus gives each player their own field, co-op players share one
+    let players_per_field = player_count.div_ceil(fields.len());
+    for index in 0..player_count {
+        let field = fields[index / players_per_field];
+        let x = player_start_x(index % players_per_field, players_per_field, &field);
+        let cannon = commands
+            .spawn((
+                player(
+                    index,
+                    Vec2::new(x, play_area.player_y),
+                    player_count > 1,
+                    500.0,
+                    &player_assets,
+                ),
+                field,
+                ChildOf(level),
+            ))
+            .id();
+        // solo keeps the rainbow reticle, co-op reticles match their cannon
+        let hue = (player_count > 1).then(|| player_hue(index));
+        commands.spawn((
+            reticle(&reticle_assets, cannon, hue),
+            Name::new("Reticle"),
+            ChildOf(level),
+        ));
+        if player_count > 1 {
+            commands.spawn(player_score_ui(index, &score_ui_assets));
+        }
+    }
+    // puzzles have a fixed set of enemies, so nothing should spawn more
+    if *game_mode != GameMode::Puzzle {
+        for field in &fields {
+            commands
+                .entity(level)
+                .with_child((EnemyController::default(), *field));
+        }
+    }
+    // versus players are scored separately, so there's no team score
+    if *game_mode != GameMode::Versus {
+        commands.spawn(score_ui(&score_ui_assets));
+    }
     commands.spawn(combo_ui(&score_ui_assets));
-    spawn_buildings(&building_assets, &mut texture_atlas_layouts, &mut commands);
+    commands.spawn(hud_ui(
+        &score_ui_assets,
+        *game_mode,
+        player_count,
+        player_count > 1,
+    ));
+    if game_mode.time_limit().is_some() {
+        commands.spawn(time_left_ui(&score_ui_assets));
+    }
+    spawn_buildings(
+        *game_mode,
+        &play_area,
+        &building_assets,
+        &mut game_rng,
+        &mut texture_atlas_layouts,
+        &mut commands,
+    );
+}
+
+/// Levels it takes for the music to reach full intensity on its own.
+const INTENSITY_LEVELS: f32 = 8.0;
+/// Enemies on screen for full intensity.
+const INTENSITY_ENEMIES: f32 = 30.0;
+/// Combo for full intensity.
+const INTENSITY_COMBO: f32 = 15.0;
+/// How quickly the music picks up when things get hectic, and how slowly it
+/// settles afterwards, in full intensity per second.
+const INTENSITY_RISE_SPEED: f32 = 1.0;
+const INTENSITY_FALL_SPEED: f32 = 0.15;
+
+/// The further into the run, the more enemies about and the longer the
+/// chain, the more intense the music.
+fn update_music_intensity(
+    controller_query: Query<&EnemyController>,
+    enemy_query: Query<(), With<Enemy>>,
+    score_controller_query: Query<&ScoreController>,
+    mut intensity: ResMut<MusicIntensity>,
+    time: Res<Time<Real>>,
+) {
+    let level = controller_query
+        .iter()
+        .map(|controller| controller.level)
+        .max()
+        .unwrap_or(1);
+    let combo = score_controller_query
+        .iter()
+        .map(ScoreController::combo)
+        .max()
+        .unwrap_or_default();
+    let level_term = (level.saturating_sub(1) as f32 / INTENSITY_LEVELS).min(1.0);
+    let enemy_term = (enemy_query.iter().len() as f32 / INTENSITY_ENEMIES).min(1.0);
+    let combo_term = (combo as f32 / INTENSITY_COMBO).min(1.0);
+    let target = (0.4 * level_term + 0.3 * enemy_term + 0.3 * combo_term).min(1.0);
+    let dt = time.delta_secs();
+    intensity.0 +=
+        (target - intensity.0).clamp(-INTENSITY_FALL_SPEED * dt, INTENSITY_RISE_SPEED * dt);
+}
+
+fn reset_music_intensity(mut intensity: ResMut<MusicIntensity>) {
+    intensity.0 = 0.0;
 }
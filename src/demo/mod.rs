@@ -5,22 +5,35 @@
 
 use bevy::prelude::*;
 
-mod buildings;
-mod enemies;
-mod explosions;
-mod floating_text;
+pub mod buildings;
+pub mod camera_effects;
+pub mod daily;
+pub mod difficulty;
+pub mod enemies;
+pub mod explosions;
+pub mod field;
+pub mod floating_text;
+mod hud;
 pub mod level;
+pub mod mode;
 mod movement;
+pub mod particles;
 pub mod player;
+pub mod puzzle;
 mod reticle;
+pub mod rng;
 pub mod score;
 mod shooting;
 mod shot;
+pub mod simulation;
+pub mod tutorial;
+pub mod versus;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         level::plugin,
         floating_text::plugin,
+        hud::plugin,
         movement::plugin,
         player::plugin,
         shot::plugin,
@@ -30,5 +43,20 @@ pub(super) fn plugin(app: &mut App) {
         reticle::plugin,
         score::plugin,
         buildings::plugin,
+        particles::plugin,
+        simulation::plugin,
+        camera_effects::plugin,
+    ));
+
+    // Game modes and the rules that change between them.
+    app.add_plugins((
+        daily::plugin,
+        difficulty::plugin,
+        field::plugin,
+        mode::plugin,
+        puzzle::plugin,
+        rng::plugin,
+        tutorial::plugin,
+        versus::plugin,
     ));
 }
@@ -0,0 +1,178 @@
+//! Game modes, the per-run clock and per-mode high scores.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{hud::hud_frame, score::ScoreUIAssets};
+use crate::{
+    AppSystems, PausableSystems,
+    screens::{Screen, in_play},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameMode>();
+    app.register_type::<RunClock>();
+    app.register_type::<TimeLeftVal>();
+    app.init_resource::<GameMode>();
+    app.init_resource::<RunClock>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_run_clock);
+    app.add_systems(OnEnter(Screen::Tutorial), reset_run_clock);
+    app.add_systems(
+        FixedUpdate,
+        tick_run_clock
+            .run_if(in_play)
+            .in_set(AppSystems::TickTimers)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        update_time_left_ui
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// The rules the current run is played under. Picked from the mode select menu.
+#[derive(
+    Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Resource)]
+pub enum GameMode {
+    /// Play until every building is gone.
+    #[default]
+    Endless,
+    /// Score as much as possible before the clock runs out.
+    TimeAttack,
+    /// A single building and no second chances.
+    Survival,
+    /// Same seed and rule modifiers for everyone playing today.
+    Daily,
+    /// Handcrafted layouts to clear with a limited number of shots.
+    Puzzle,
+    /// Two players on split fields, sending enemies at each other with big chains.
+    Versus,
+}
+
+const TIME_ATTACK_LENGTH: f32 = 180.0;
+
+impl GameMode {
+    /// Modes that start straight from the mode select menu. Puzzle mode goes
+    /// through its own level select instead.
+    pub const QUICK_START: [GameMode; 5] = [
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Survival,
+        GameMode::Daily,
+        GameMode::Versus,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Survival => "Survival",
+            GameMode::Daily => "Daily",
+            GameMode::Puzzle => "Puzzle",
+            GameMode::Versus => "Versus",
+        }
+    }
+
+    /// How long a run lasts, if it's capped.
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::TimeAttack => Some(TIME_ATTACK_LENGTH),
+            GameMode::Endless
+            | GameMode::Survival
+            | GameMode::Daily
+            | GameMode::Puzzle
+            | GameMode::Versus => None,
+        }
+    }
+}
+
+/// Seconds of unpaused gameplay in the current run.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RunClock(pub f32);
+
+impl RunClock {
+    /// Whether the current mode's time limit (if any) has been reached.
+    pub fn is_out_of_time(&self, game_mode: GameMode) -> bool {
+        game_mode.time_limit().is_some_and(|limit| self.0 >= limit)
+    }
+}
+
+fn reset_run_clock(mut run_clock: ResMut<RunClock>) {
+    run_clock.0 = 0.0;
+}
+
+fn tick_run_clock(mut run_clock: ResMut<RunClock>, time: Res<Time>) {
+    run_clock.0 += time.delta_secs();
+}
+
+const MAX_HIGH_SCORES: usize = 5;
+
+/// The best scores for each mode, highest first. Kept in the save data.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HighScores(HashMap<GameMode, Vec<u32>>);
+
+impl HighScores {
+    pub fn get(&self, game_mode: GameMode) -> &[u32] {
+        self.0.get(&game_mode).map_or(&[], Vec::as_slice)
+    }
+
+    /// Records a finished run, returning true if it's the new best for that mode.
+    pub fn record(&mut self, game_mode: GameMode, score: u32) -> bool {
+        let scores = self.0.entry(game_mode).or_default();
+        let is_new_best = scores.first().is_none_or(|&best| score > best);
+        let index = scores.partition_point(|&s| s >= score);
+        scores.insert(index, score);
+        scores.truncate(MAX_HIGH_SCORES);
+        is_new_best
+    }
+}
+
+/// A box showing how long is left in the run. Only spawned for timed modes.
+pub fn time_left_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    (
+        hud_frame(
+            score_ui_assets,
+            Node {
+                position_type: PositionType::Absolute,
+                align_items: AlignItems::Center,
+                top: Val::Px(5.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-50.0)),
+                width: Val::Px(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+        ),
+        children![
+            (Text::new("time"), TextFont::from_font_size(18.0),),
+            (TimeLeftVal, Text::new(""), TextFont::from_font_size(24.0),)
+        ],
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TimeLeftVal;
+
+fn update_time_left_ui(
+    game_mode: Res<GameMode>,
+    run_clock: Res<RunClock>,
+    mut time_text: Query<&mut Text, With<TimeLeftVal>>,
+) {
+    let Some(limit) = game_mode.time_limit() else {
+        return;
+    };
+    let remaining = (limit - run_clock.0).max(0.0).ceil() as u32;
+    for mut text in time_text.iter_mut() {
+        text.0 = format!("{}:{:02}", remaining / 60, remaining % 60);
+    }
+}
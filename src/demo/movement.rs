@@ -7,21 +7,22 @@
 //!   This is done in the `player` module, as it is specific to the player
 //!   character.
 //! - Apply movement based on [`MovementController`] intent and maximum speed.
-//! - Wrap the character within the window.
+//! - Keep the character within its [`PlayField`].
 //!
-//! Note that the implementation used here is limited for demonstration
-//! purposes. If you want to move the player in a smoother way,
-//! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
+//! Movement runs on the fixed gameplay tick, and is drawn smoothly between
+//! ticks by the `simulation` module.
 
 use bevy::prelude::*;
 
+use super::field::{PlayArea, PlayField};
 use crate::{AppSystems, PausableSystems};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MovementController>();
+    app.add_event::<HitEdge>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (apply_movement)
             .chain()
             .in_set(AppSystems::Update)
@@ -41,6 +42,9 @@ pub struct MovementController {
     /// Maximum speed in world units per second.
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics engine.
     pub max_speed: f32,
+
+    /// Whether the last move got stopped at the edge of the field.
+    pub at_edge: bool,
 }
 
 impl Default for MovementController {
@@ -49,19 +53,39 @@ impl Default for MovementController {
             intent: Vec2::ZERO,
             // 400 pixels per second is a nice default, but we can still vary this per character.
             max_speed: 400.0,
+            at_edge: false,
         }
     }
 }
 
-pub const MAX_X: f32 = 330.0;
+/// Sent when a character runs into the edge of its field, but not again while
+/// it keeps pushing against it.
+#[derive(Event, Debug)]
+pub struct HitEdge {
+    pub position: Vec2,
+}
 
 fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&MovementController, &mut Transform)>,
+    play_area: Res<PlayArea>,
+    mut movement_query: Query<(&mut MovementController, &mut Transform, Option<&PlayField>)>,
+    mut ev_hit_edge: EventWriter<HitEdge>,
 ) {
-    for (controller, mut transform) in &mut movement_query {
+    for (mut controller, mut transform, field) in &mut movement_query {
         let velocity = controller.max_speed * controller.intent;
         transform.translation += velocity.extend(0.0) * time.delta_secs();
-        transform.translation.x = transform.translation.x.clamp(-MAX_X, MAX_X);
+        let unclamped_x = transform.translation.x;
+        transform.translation.x = field
+            .copied()
+            .unwrap_or_else(|| PlayField::whole(&play_area))
+            .clamp_x(unclamped_x);
+
+        let at_edge = transform.translation.x != unclamped_x;
+        if at_edge && !controller.at_edge {
+            ev_hit_edge.write(HitEdge {
+                position: transform.translation.truncate(),
+            });
+        }
+        controller.at_edge = at_edge;
     }
 }
@@ -0,0 +1,335 @@
+//! Lightweight CPU particles: sparks, smoke and rubble. What each effect looks
+//! like is data, loaded from `assets/particles/effects.particles.ron`, and
+//! anything can set one off with a [`NewParticles`] event.
+//!
+//! Particles are only for show, so they use their own randomness rather than
+//! the run's seeded streams and don't need to match between online players.
+
+use std::{error::Error, f32::consts::PI};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{enemies::ENEMY_EXPLOSION_RADIUS, explosions::Explosion, score::ScoreController};
+use crate::{AppSystems, PausableSystems, asset_tracking::LoadResource, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<ParticleEffects>();
+    app.init_asset_loader::<ParticleEffectsLoader>();
+
+    app.register_type::<ParticleAssets>();
+    app.load_resource::<ParticleAssets>();
+
+    app.register_type::<Particle>();
+    app.register_type::<ParticleQuality>();
+    app.init_resource::<ParticleQuality>();
+    app.add_event::<NewParticles>();
+
+    app.add_systems(
+        Update,
+        (
+            emit_explosion_particles,
+            handle_new_particles_event,
+            update_particles,
+        )
+            .chain()
+            .run_if(resource_exists::<ParticleAssets>)
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// The effects the game knows how to play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleEffect {
+    Explosion,
+    BuildingHit,
+    BuildingDestroyed,
+    BuildingSmoke,
+    BuildingFire,
+}
+
+/// Plays `effect` at a position. `scale` makes it bigger or smaller than
+/// usual: more particles, flying further.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct NewParticles {
+    pub effect: ParticleEffect,
+    pub position: Vec2,
+    pub scale: f32,
+}
+
+impl NewParticles {
+    pub fn new(effect: ParticleEffect, position: Vec2) -> Self {
+        Self {
+            effect,
+            position,
+            scale: 1.0,
+        }
+    }
+}
+
+/// How many particles to draw. Picked in the settings menu.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum ParticleQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ParticleQuality {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Low, Self::Medium, Self::High];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        }
+    }
+
+    fn count_multiplier(self) -> f32 {
+        match self {
+            Self::Off => 0.0,
+            Self::Low => 0.4,
+            Self::Medium => 1.0,
+            Self::High => 2.0,
+        }
+    }
+}
+
+/// Every effect, as loaded from the effects file.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct ParticleEffects {
+    explosion: Vec<Emitter>,
+    building_hit: Vec<Emitter>,
+    building_destroyed: Vec<Emitter>,
+    building_smoke: Vec<Emitter>,
+    building_fire: Vec<Emitter>,
+}
+
+impl ParticleEffects {
+    fn emitters(&self, effect: ParticleEffect) -> &[Emitter] {
+        match effect {
+            ParticleEffect::Explosion => &self.explosion,
+            ParticleEffect::BuildingHit => &self.building_hit,
+            ParticleEffect::BuildingDestroyed => &self.building_destroyed,
+            ParticleEffect::BuildingSmoke => &self.building_smoke,
+            ParticleEffect::BuildingFire => &self.building_fire,
+        }
+    }
+}
+
+/// One burst of particles that all look alike. See the effects file for what
+/// each field means.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+struct Emitter {
+    count: u32,
+    speed: (f32, f32),
+    lifetime: (f32, f32),
+    size: (f32, f32),
+    start_color: (f32, f32, f32, f32),
+    end_color: (f32, f32, f32, f32),
+    /// Pulls particles down, or up if negative, in pixels per second squared.
+    gravity: f32,
+    /// How quickly particles lose speed, as a fraction per second.
+    drag: f32,
+    direction: f32,
+    spread: f32,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            speed: (50.0, 100.0),
+            lifetime: (0.5, 1.0),
+            size: (2.0, 2.0),
+            start_color: (1.0, 1.0, 1.0, 1.0),
+            end_color: (1.0, 1.0, 1.0, 0.0),
+            gravity: 0.0,
+            drag: 0.0,
+            direction: 90.0,
+            spread: 360.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ParticleEffectsLoader;
+
+impl AssetLoader for ParticleEffectsLoader {
+    type Asset = ParticleEffects;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particles.ron"]
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ParticleAssets {
+    #[dependency]
+    effects: Handle<ParticleEffects>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            effects: assets.load("particles/effects.particles.ron"),
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+struct Particle {
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    start_size: f32,
+    end_size: f32,
+    start_color: Color,
+    end_color: Color,
+    gravity: f32,
+    drag: f32,
+}
+
+/// Past this many particles on screen, new ones are skipped.
+const MAX_PARTICLES: usize = 3000;
+const PARTICLE_Z: f32 = 4.0;
+/// Every link in a combo makes explosions this much bigger...
+const COMBO_SCALE_PER_LINK: f32 = 0.05;
+/// ...up to this many times their usual size.
+const MAX_COMBO_SCALE: f32 = 2.5;
+
+/// Explosions scale with their blast radius and with the combo they're part
+/// of, so a long chain builds up to a proper fireworks show.
+fn emit_explosion_particles(
+    explosion_query: Query<(&Transform, &Explosion), Added<Explosion>>,
+    score_controller_query: Query<&ScoreController>,
+    mut ev_new_particles: EventWriter<NewParticles>,
+) {
+    let combo = score_controller_query
+        .iter()
+        .map(ScoreController::combo)
+        .max()
+        .unwrap_or(1);
+    let combo_scale =
+        (1.0 + combo.saturating_sub(1) as f32 * COMBO_SCALE_PER_LINK).min(MAX_COMBO_SCALE);
+    for (transform, explosion) in &explosion_query {
+        ev_new_particles.write(NewParticles {
+            effect: ParticleEffect::Explosion,
+            position: transform.translation.truncate(),
+            scale: explosion.radius / ENEMY_EXPLOSION_RADIUS * combo_scale,
+        });
+    }
+}
+
+fn handle_new_particles_event(
+    mut ev_new_particles: EventReader<NewParticles>,
+    mut commands: Commands,
+    particle_assets: Res<ParticleAssets>,
+    effects: Res<Assets<ParticleEffects>>,
+    quality: Res<ParticleQuality>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    let Some(effects) = effects.get(&particle_assets.effects) else {
+        ev_new_particles.clear();
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    let mut particle_count = particle_query.iter().len();
+    for ev in ev_new_particles.read() {
+        // bigger blasts get more particles, and throw them further
+        let count_scale = quality.count_multiplier() * ev.scale;
+        let speed_scale = ev.scale.sqrt();
+        for emitter in effects.emitters(ev.effect) {
+            let count = (emitter.count as f32 * count_scale).round() as usize;
+            let count = count.min(MAX_PARTICLES.saturating_sub(particle_count));
+            particle_count += count;
+            for _ in 0..count {
+                commands.spawn(particle(emitter, ev.position, speed_scale, &mut rng));
+            }
+        }
+    }
+}
+
+fn particle(
+    emitter: &Emitter,
+    position: Vec2,
+    speed_scale: f32,
+    rng: &mut impl Rng,
+) -> impl Bundle {
+    let half_spread = emitter.spread.to_radians() / 2.0;
+    let angle = emitter.direction.to_radians() + rng.gen_range(-half_spread..=half_spread);
+    let speed = rng.gen_range(emitter.speed.0..=emitter.speed.1) * speed_scale;
+    let (r, g, b, a) = emitter.start_color;
+    let start_color = Color::srgba(r, g, b, a);
+    let (r, g, b, a) = emitter.end_color;
+    let end_color = Color::srgba(r, g, b, a);
+    (
+        Name::new("Particle"),
+        Particle {
+            velocity: Vec2::from_angle(angle) * speed,
+            age: 0.0,
+            lifetime: rng.gen_range(emitter.lifetime.0..=emitter.lifetime.1),
+            start_size: emitter.size.0,
+            end_size: emitter.size.1,
+            start_color,
+            end_color,
+            gravity: emitter.gravity,
+            drag: emitter.drag,
+        },
+        Sprite::from_color(start_color, Vec2::ONE),
+        Transform::from_translation(position.extend(PARTICLE_Z))
+            .with_rotation(Quat::from_rotation_z(rng.gen_range(0.0..PI)))
+            .with_scale(Vec3::splat(emitter.size.0)),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in &mut query {
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let drag = (1.0 - particle.drag * dt).max(0.0);
+        particle.velocity.y -= particle.gravity * dt;
+        particle.velocity *= drag;
+        transform.translation += (particle.velocity * dt).extend(0.0);
+
+        let t = particle.age / particle.lifetime;
+        transform.scale = Vec3::splat(particle.start_size.lerp(particle.end_size, t));
+        sprite.color = particle.start_color.mix(&particle.end_color, t);
+    }
+}
@@ -4,51 +4,123 @@ use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
+use rand::Rng;
 
 use crate::{
-    AppSystems, PausableSystems, asset_tracking::LoadResource, demo::movement::MovementController,
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::{PlaySfx, SfxCategory},
+    demo::movement::{HitEdge, MovementController},
 };
 
-use super::shooting::ShootingController;
+use super::{
+    field::PlayField, mode::GameMode, shooting::ShootingController, simulation::Interpolated,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Player>();
+    app.register_type::<PlayerCount>();
+    app.init_resource::<PlayerCount>();
 
     app.register_type::<PlayerAssets>();
     app.load_resource::<PlayerAssets>();
 
-    // Record directional input as movement controls.
+    app.register_type::<Treads>();
     app.add_systems(
         Update,
-        record_player_directional_input
-            .in_set(AppSystems::RecordInput)
+        (play_tread_sounds, play_edge_bump_sounds)
+            .run_if(resource_exists::<PlayerAssets>)
+            .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
 
-    // Record spacebar as shooting input.
+    // Record each player's keys or gamepad every frame, then turn that into
+    // movement and shooting controls on the next gameplay tick.
+    app.register_type::<PlayerInput>();
     app.add_systems(
         Update,
-        record_player_shooting_input
+        record_player_input
             .in_set(AppSystems::RecordInput)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        FixedUpdate,
+        apply_player_input
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Horizontal gap between cannons sharing a field.
+const PLAYER_SPACING: f32 = 200.0;
+
+/// Local co-op supports up to this many players.
+pub const MAX_PLAYERS: usize = 2;
+
+/// How many people are playing on this machine. Picked in the settings menu.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl PlayerCount {
+    /// How many cannons a run of the given mode gets. Puzzles are balanced
+    /// around a single cannon's shots, so they're always solo, and versus
+    /// always needs both players.
+    pub fn for_mode(self, game_mode: GameMode) -> usize {
+        match game_mode {
+            GameMode::Puzzle => 1,
+            GameMode::Versus => MAX_PLAYERS,
+            GameMode::Endless | GameMode::TimeAttack | GameMode::Survival | GameMode::Daily => {
+                self.0.clamp(1, MAX_PLAYERS)
+            }
+        }
+    }
+}
+
+/// Each player's colour in co-op, as a hue.
+const PLAYER_HUES: [f32; MAX_PLAYERS] = [200.0, 30.0];
+
+pub fn player_hue(index: usize) -> f32 {
+    PLAYER_HUES[index % MAX_PLAYERS]
 }
 
-const PLAYER_Y: f32 = -220.0;
+pub fn player_color(index: usize) -> Color {
+    Color::hsl(player_hue(index), 0.9, 0.7)
+}
+
+/// Where the cannon in `slot` starts, out of the `players_on_field` sharing `field`.
+pub fn player_start_x(slot: usize, players_on_field: usize, field: &PlayField) -> f32 {
+    field.center_x + (slot as f32 - (players_on_field - 1) as f32 / 2.0) * PLAYER_SPACING
+}
 
-/// The player character.
-pub fn player(max_speed: f32, player_assets: &PlayerAssets) -> impl Bundle {
+/// The player character. `index` is which player controls it, and `tinted`
+/// gives it that player's colour so cannons can be told apart.
+pub fn player(
+    index: usize,
+    position: Vec2,
+    tinted: bool,
+    max_speed: f32,
+    player_assets: &PlayerAssets,
+) -> impl Bundle {
     let mut initial_transform = Transform::from_scale(Vec2::splat(2.0).extend(1.0));
-    initial_transform.translation = Vec3 {
-        x: 0.0,
-        y: PLAYER_Y,
-        z: 0.0,
+    initial_transform.translation = position.extend(0.0);
+    let color = if tinted {
+        player_color(index)
+    } else {
+        Color::WHITE
     };
     (
-        Name::new("Player"),
-        Player,
+        Name::new(format!("Player {}", index + 1)),
+        Player { index },
         Sprite {
             image: player_assets.cannon.clone(),
+            color,
             ..default()
         },
         initial_transform,
@@ -57,55 +129,153 @@ pub fn player(max_speed: f32, player_assets: &PlayerAssets) -> impl Bundle {
             ..default()
         },
         ShootingController::default(),
+        PlayerInput::default(),
+        Treads::default(),
+        Interpolated::default(),
     )
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
-struct Player;
+pub struct Player {
+    /// Which player this is, starting from 0.
+    pub index: usize,
+}
 
-const PLAYER_RETICLE_Y_SPEED: f32 = 10.0;
+/// The keys one player uses.
+struct KeySet {
+    up: KeyCode,
+    down: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+    fire: KeyCode,
+}
 
-fn record_player_directional_input(
-    input: Res<ButtonInput<KeyCode>>,
-    mut controller_query: Query<(&mut MovementController, &mut ShootingController), With<Player>>,
-) {
-    // Collect directional input.
-    let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
-        intent.y += 1.0;
-    }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
-        intent.y -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        intent.x -= 1.0;
+const KEY_SETS: [KeySet; MAX_PLAYERS] = [
+    KeySet {
+        up: KeyCode::KeyW,
+        down: KeyCode::KeyS,
+        left: KeyCode::KeyA,
+        right: KeyCode::KeyD,
+        fire: KeyCode::Space,
+    },
+    KeySet {
+        up: KeyCode::ArrowUp,
+        down: KeyCode::ArrowDown,
+        left: KeyCode::ArrowLeft,
+        right: KeyCode::ArrowRight,
+        fire: KeyCode::Enter,
+    },
+];
+
+impl Player {
+    /// Playing solo, either key set works. In co-op each player gets their own.
+    fn key_sets(self, player_count: usize) -> &'static [KeySet] {
+        if player_count > 1 {
+            std::slice::from_ref(&KEY_SETS[self.index % MAX_PLAYERS])
+        } else {
+            &KEY_SETS
+        }
     }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        intent.x += 1.0;
+
+    /// The first gamepad goes to the last player, so it's player one's when
+    /// playing solo and player two's in co-op.
+    fn uses_gamepad(self, player_count: usize) -> bool {
+        self.index + 1 == player_count
     }
+}
 
-    // Normalize intent so that diagonal movement is the same speed as horizontal / vertical.
-    // This should be omitted if the input comes from an analog stick instead.
-    let intent = intent.normalize_or_zero();
+/// How fast up/down moves the reticle, in pixels per second.
+const PLAYER_RETICLE_Y_SPEED: f32 = 600.0;
 
-    // Apply movement intent to controllers.
-    for (mut movement_controller, mut shooting_controller) in &mut controller_query {
-        movement_controller.intent = Vec2 {
-            x: intent.x,
-            y: 0.0,
-        };
-        shooting_controller.target_offset += Vec2::new(0.0, intent.y * PLAYER_RETICLE_Y_SPEED);
-        shooting_controller.intent_to_fire = input.just_pressed(KeyCode::Space);
+/// What a player wants to do on the next tick. Usually read from their keys or
+/// gamepad, but online play swaps in the inputs agreed with the other game.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct PlayerInput {
+    /// Left/right moves the cannon, up/down moves the reticle.
+    pub movement: Vec2,
+    /// Held until a tick uses it, so a quick tap between ticks still fires.
+    pub fire: bool,
+}
+
+/// Input from every key set and the first gamepad, for when one person has
+/// the whole keyboard to themselves.
+pub fn local_input(input: &ButtonInput<KeyCode>, gamepad: Option<&Gamepad>) -> PlayerInput {
+    read_input(&KEY_SETS, input, gamepad)
+}
+
+fn read_input(
+    key_sets: &[KeySet],
+    input: &ButtonInput<KeyCode>,
+    gamepad: Option<&Gamepad>,
+) -> PlayerInput {
+    // Collect directional input.
+    let mut movement = Vec2::ZERO;
+    for keys in key_sets {
+        if input.pressed(keys.up) {
+            movement.y += 1.0;
+        }
+        if input.pressed(keys.down) {
+            movement.y -= 1.0;
+        }
+        if input.pressed(keys.left) {
+            movement.x -= 1.0;
+        }
+        if input.pressed(keys.right) {
+            movement.x += 1.0;
+        }
     }
+    // Normalize so that diagonal movement is the same speed as horizontal / vertical.
+    let mut movement = movement.normalize_or_zero();
+    let mut fire = key_sets.iter().any(|keys| input.just_pressed(keys.fire));
+
+    // Analog sticks are left as-is so a light push moves slowly.
+    if let Some(gamepad) = gamepad {
+        movement = (movement + gamepad.left_stick() + gamepad.dpad()).clamp_length_max(1.0);
+        fire |= gamepad.just_pressed(GamepadButton::South);
+    }
+    PlayerInput { movement, fire }
 }
 
-fn record_player_shooting_input(
+pub fn record_player_input(
     input: Res<ButtonInput<KeyCode>>,
-    mut shooting_query: Query<&mut ShootingController, With<Player>>,
+    gamepads: Query<&Gamepad>,
+    mut player_query: Query<(&Player, &mut PlayerInput)>,
 ) {
-    for mut controller in &mut shooting_query {
-        controller.intent_to_fire = input.just_pressed(KeyCode::Space);
+    let player_count = player_query.iter().count();
+    let gamepad = gamepads.iter().next();
+    for (player, mut player_input) in &mut player_query {
+        let latest = read_input(
+            player.key_sets(player_count),
+            &input,
+            gamepad.filter(|_| player.uses_gamepad(player_count)),
+        );
+        player_input.movement = latest.movement;
+        player_input.fire |= latest.fire;
+    }
+}
+
+/// Turns each player's input into intents for their cannon's controllers.
+pub fn apply_player_input(
+    time: Res<Time>,
+    mut controller_query: Query<(
+        &mut PlayerInput,
+        &mut MovementController,
+        &mut ShootingController,
+    )>,
+) {
+    for (mut input, mut movement_controller, mut shooting_controller) in &mut controller_query {
+        movement_controller.intent = Vec2 {
+            x: input.movement.x,
+            y: 0.0,
+        };
+        shooting_controller.target_offset += Vec2::new(
+            0.0,
+            input.movement.y * PLAYER_RETICLE_Y_SPEED * time.delta_secs(),
+        );
+        shooting_controller.intent_to_fire = input.fire;
+        input.fire = false;
     }
 }
 
@@ -138,3 +308,64 @@ impl FromWorld for PlayerAssets {
         }
     }
 }
+
+/// Counts down to the cannon's next tread sound while it's moving.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+struct Treads {
+    until_next_step: f32,
+}
+
+/// Seconds between tread sounds at full speed. Slower movement, like a light
+/// push on the stick, spaces them out...
+const STEP_INTERVAL: f32 = 0.18;
+/// ...up to this fraction of full speed, so creeping along doesn't go silent.
+const MIN_STEP_SPEED: f32 = 0.3;
+/// The edge bump is a step slowed right down into a heavy clunk.
+const EDGE_BUMP_PITCH: f32 = 0.5;
+
+fn random_step(player_assets: &PlayerAssets) -> Handle<AudioSource> {
+    let index = rand::thread_rng().gen_range(0..player_assets.steps.len());
+    player_assets.steps[index].clone()
+}
+
+fn play_tread_sounds(
+    time: Res<Time>,
+    player_assets: Res<PlayerAssets>,
+    mut player_query: Query<(&MovementController, &Transform, &mut Treads)>,
+    mut ev_play_sfx: EventWriter<PlaySfx>,
+) {
+    for (controller, transform, mut treads) in &mut player_query {
+        // stopping, or pushing against the edge, means the next move starts
+        // with a step straight away
+        if controller.intent == Vec2::ZERO || controller.at_edge {
+            treads.until_next_step = 0.0;
+            continue;
+        }
+        treads.until_next_step -= time.delta_secs();
+        if treads.until_next_step > 0.0 {
+            continue;
+        }
+        let speed = controller.intent.length().clamp(MIN_STEP_SPEED, 1.0);
+        treads.until_next_step = STEP_INTERVAL / speed;
+        ev_play_sfx.write(
+            PlaySfx::new(random_step(&player_assets), SfxCategory::Movement)
+                .at(transform.translation.truncate()),
+        );
+    }
+}
+
+fn play_edge_bump_sounds(
+    mut ev_hit_edge: EventReader<HitEdge>,
+    player_assets: Res<PlayerAssets>,
+    mut ev_play_sfx: EventWriter<PlaySfx>,
+) {
+    for ev in ev_hit_edge.read() {
+        ev_play_sfx.write(
+            PlaySfx::new(random_step(&player_assets), SfxCategory::Movement)
+                .with_priority(1.0)
+                .with_pitch(EDGE_BUMP_PITCH)
+                .at(ev.position),
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Puzzle mode: handcrafted layouts of stationary enemies that have to be
+//! cleared with a limited number of shots. Levels are loaded from
+//! `assets/puzzles/levels.puzzles.ron`.
+
+use std::error::Error;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{
+    enemies::{ENEMY_EXPLOSION_RADIUS, EnemyAssets, stationary_enemy},
+    explosions::Explosion,
+    floating_text::NewText,
+    hud::hud_frame,
+    level::spawn_level,
+    mode::GameMode,
+    score::ScoreUIAssets,
+    shooting::{ShootingController, shoot_if_we_shooting},
+    shot::Shot,
+};
+use crate::{
+    AppSystems, PausableSystems, asset_tracking::LoadResource, save::SaveData, screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<PuzzlePack>();
+    app.init_asset_loader::<PuzzlePackLoader>();
+
+    app.register_type::<PuzzleAssets>();
+    app.load_resource::<PuzzleAssets>();
+
+    app.register_type::<CurrentPuzzle>();
+    app.register_type::<PuzzleEnemy>();
+    app.register_type::<ShotsLeftVal>();
+    app.init_resource::<CurrentPuzzle>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        setup_puzzle
+            .after(spawn_level)
+            .run_if(resource_equals(GameMode::Puzzle)),
+    );
+    // after shooting, with a sync point in between, so the last shot is
+    // already in flight when we check whether the player is out of shots
+    app.add_systems(
+        FixedUpdate,
+        check_for_puzzle_end
+            .after(shoot_if_we_shooting)
+            .run_if(in_state(Screen::Gameplay).and(resource_equals(GameMode::Puzzle)))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        update_shots_left_ui
+            .run_if(in_state(Screen::Gameplay).and(resource_equals(GameMode::Puzzle)))
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// Every puzzle level, in play order.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct PuzzlePack {
+    pub levels: Vec<PuzzleLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PuzzleLevel {
+    pub name: String,
+    /// How many shots the player gets.
+    pub shots: u32,
+    /// Clearing the level in this many shots (or fewer) is worth three stars.
+    pub par: u32,
+    pub enemies: Vec<PuzzleEnemySpawn>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct PuzzleEnemySpawn {
+    pub x: f32,
+    pub y: f32,
+    /// Overrides the usual enemy explosion radius.
+    #[serde(default)]
+    pub radius: Option<f32>,
+}
+
+pub const MAX_STARS: u8 = 3;
+
+impl PuzzleLevel {
+    pub fn stars(&self, shots_used: u32) -> u8 {
+        if shots_used <= self.par {
+            MAX_STARS
+        } else if shots_used <= self.par + 1 {
+            MAX_STARS - 1
+        } else {
+            1
+        }
+    }
+}
+
+#[derive(Default, TypePath)]
+struct PuzzlePackLoader;
+
+impl AssetLoader for PuzzlePackLoader {
+    type Asset = PuzzlePack;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzles.ron"]
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct PuzzleAssets {
+    #[dependency]
+    pack: Handle<PuzzlePack>,
+}
+
+impl FromWorld for PuzzleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            pack: assets.load("puzzles/levels.puzzles.ron"),
+        }
+    }
+}
+
+impl PuzzleAssets {
+    pub fn levels<'a>(&self, packs: &'a Assets<PuzzlePack>) -> &'a [PuzzleLevel] {
+        packs
+            .get(&self.pack)
+            .map_or(&[], |pack| pack.levels.as_slice())
+    }
+}
+
+/// Index of the puzzle level being played (or last played).
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentPuzzle(pub usize);
+
+/// Whether a level is available to play. Each level unlocks once the one before it is cleared.
+pub fn is_unlocked(levels: &[PuzzleLevel], index: usize, save_data: &SaveData) -> bool {
+    index == 0
+        || levels
+            .get(index - 1)
+            .is_some_and(|previous| save_data.puzzle_stars.contains_key(&previous.name))
+}
+
+/// How the last puzzle attempt went, for the game over screen.
+#[derive(Resource, Debug)]
+pub struct PuzzleResult {
+    pub cleared: bool,
+    pub stars: u8,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PuzzleEnemy;
+
+fn setup_puzzle(
+    mut commands: Commands,
+    puzzle_assets: Res<PuzzleAssets>,
+    packs: Res<Assets<PuzzlePack>>,
+    current_puzzle: Res<CurrentPuzzle>,
+    enemy_assets: Res<EnemyAssets>,
+    score_ui_assets: Res<ScoreUIAssets>,
+    mut shooting_query: Query<&mut ShootingController>,
+    mut ev_new_text: EventWriter<NewText>,
+) {
+    commands.remove_resource::<PuzzleResult>();
+    let Some(level) = puzzle_assets.levels(&packs).get(current_puzzle.0) else {
+        warn!("No puzzle level at index {}", current_puzzle.0);
+        return;
+    };
+
+    for spawn in &level.enemies {
+        commands.spawn((
+            stationary_enemy(
+                Vec2::new(spawn.x, spawn.y),
+                spawn.radius.unwrap_or(ENEMY_EXPLOSION_RADIUS),
+                &enemy_assets,
+            ),
+            PuzzleEnemy,
+        ));
+    }
+    for mut controller in shooting_query.iter_mut() {
+        controller.shots_left = Some(level.shots);
+    }
+    commands.spawn(shots_left_ui(&score_ui_assets));
+    ev_new_text.write(NewText(level.name.clone(), 0.0, -120.0));
+}
+
+fn check_for_puzzle_end(
+    mut commands: Commands,
+    puzzle_assets: Res<PuzzleAssets>,
+    packs: Res<Assets<PuzzlePack>>,
+    current_puzzle: Res<CurrentPuzzle>,
+    enemy_query: Query<(), With<PuzzleEnemy>>,
+    shot_query: Query<(), With<Shot>>,
+    explosion_query: Query<(), With<Explosion>>,
+    shooting_query: Query<&ShootingController>,
+    mut save_data: ResMut<SaveData>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(level) = puzzle_assets.levels(&packs).get(current_puzzle.0) else {
+        return;
+    };
+    // let the last explosions play out before calling it either way
+    if !explosion_query.is_empty() || !shot_query.is_empty() {
+        return;
+    }
+
+    if enemy_query.is_empty() {
+        let shots_left = shooting_query
+            .iter()
+            .filter_map(|controller| controller.shots_left)
+            .min()
+            .unwrap_or(0);
+        let stars = level.stars(level.shots.saturating_sub(shots_left));
+        let best = save_data
+            .puzzle_stars
+            .get(&level.name)
+            .copied()
+            .unwrap_or(0);
+        if stars > best {
+            save_data.puzzle_stars.insert(level.name.clone(), stars);
+        }
+        commands.insert_resource(PuzzleResult {
+            cleared: true,
+            stars,
+        });
+        next_screen.set(Screen::GameOver);
+    } else if shooting_query
+        .iter()
+        .all(|controller| controller.shots_left == Some(0))
+    {
+        commands.insert_resource(PuzzleResult {
+            cleared: false,
+            stars: 0,
+        });
+        next_screen.set(Screen::GameOver);
+    }
+}
+
+fn shots_left_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    (
+        hud_frame(
+            score_ui_assets,
+            Node {
+                position_type: PositionType::Absolute,
+                align_items: AlignItems::Center,
+                top: Val::Px(5.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-50.0)),
+                width: Val::Px(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+        ),
+        children![
+            (Text::new("shots"), TextFont::from_font_size(18.0),),
+            (ShotsLeftVal, Text::new(""), TextFont::from_font_size(24.0),)
+        ],
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ShotsLeftVal;
+
+fn update_shots_left_ui(
+    shooting_query: Query<&ShootingController>,
+    mut shots_text: Query<&mut Text, With<ShotsLeftVal>>,
+) {
+    let shots_left = shooting_query
+        .iter()
+        .filter_map(|controller| controller.shots_left)
+        .min()
+        .unwrap_or(0);
+    for mut text in shots_text.iter_mut() {
+        text.0 = format!("{shots_left}");
+    }
+}
@@ -10,6 +10,8 @@ const RETICLE_Z: f32 = 10.0;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Reticle>();
+    app.register_type::<ReticleOf>();
+    app.register_type::<Reticles>();
 
     app.register_type::<ReticleAssets>();
     app.load_resource::<ReticleAssets>();
@@ -33,7 +35,9 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-pub fn reticle(reticle_assets: &ReticleAssets) -> impl Bundle {
+/// A reticle following `owner`'s aim. With a `hue` it stays that colour,
+/// otherwise it cycles through the rainbow.
+pub fn reticle(reticle_assets: &ReticleAssets, owner: Entity, hue: Option<f32>) -> impl Bundle {
     (
         Sprite {
             image: reticle_assets.texture.clone(),
@@ -42,7 +46,8 @@ pub fn reticle(reticle_assets: &ReticleAssets) -> impl Bundle {
         Transform::default()
             .with_scale(Vec3::splat(2.0))
             .with_translation(Vec2::ZERO.extend(RETICLE_Z)),
-        Reticle::default(),
+        Reticle { hue, ..default() },
+        ReticleOf(owner),
     )
 }
 
@@ -53,6 +58,7 @@ pub struct Reticle {
     pub visual_speed: f32,
     pub visual_wave_val: f32,
     pub target: Option<Vec2>,
+    pub hue: Option<f32>,
 }
 
 impl Default for Reticle {
@@ -62,10 +68,23 @@ impl Default for Reticle {
             visual_speed: 8.0,
             visual_wave_val: 0.5,
             target: None,
+            hue: None,
         }
     }
 }
 
+/// The shooter whose aim a reticle shows.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = Reticles)]
+pub struct ReticleOf(pub Entity);
+
+/// The reticles showing a shooter's aim.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = ReticleOf)]
+pub struct Reticles(Vec<Entity>);
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct ReticleAssets {
@@ -95,11 +114,11 @@ fn update_reticle_visual_wave_val_timer(mut reticle_query: Query<&mut Reticle>,
 }
 
 fn update_reticle_target(
-    mut reticle_query: Query<&mut Reticle>,
+    mut reticle_query: Query<(&mut Reticle, &ReticleOf)>,
     shooting_controller_query: Query<(&Transform, &ShootingController)>,
 ) {
-    for mut reticle in reticle_query.iter_mut() {
-        if let Some((transform, shooting_controller)) = shooting_controller_query.iter().next() {
+    for (mut reticle, owner) in reticle_query.iter_mut() {
+        if let Ok((transform, shooting_controller)) = shooting_controller_query.get(owner.0) {
             reticle.target =
                 Some(transform.translation.truncate() + shooting_controller.target_offset);
         }
@@ -123,11 +142,15 @@ fn update_reticle_position(mut reticle_query: Query<(&mut Transform, &Reticle)>,
 fn update_reticle_visual(mut reticle_query: Query<(&Reticle, &mut Sprite)>) {
     for (reticle, mut sprite) in reticle_query.iter_mut() {
         // adjust hue of sprite across color spectrum based on visual_wave_val (between 0.0 and 1.0)
-        // (sprite is grayscale)
+        // (sprite is grayscale). reticles with their own hue pulse in brightness instead
+        let (hue, lightness) = match reticle.hue {
+            Some(hue) => (hue, 0.4 + reticle.visual_wave_val * 0.3),
+            None => (reticle.visual_wave_val * 255.0, 0.5),
+        };
         sprite.color = Color::Hsla(Hsla {
-            hue: reticle.visual_wave_val * 255.0,
+            hue,
             saturation: 0.9,
-            lightness: 0.5,
+            lightness,
             alpha: 1.0,
         });
     }
@@ -0,0 +1,75 @@
+//! Seeded randomness for anything that should come out the same for everyone
+//! playing the same seed, like the building layout and enemy paths. Online
+//! play also relies on this to keep both games in step.
+//! Purely cosmetic randomness (which boom sound plays, ouch text) doesn't go
+//! through here.
+
+use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+
+use super::{daily, level::spawn_level, mode::GameMode};
+use crate::screens::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RunSeed>();
+    app.init_resource::<RunSeed>();
+    app.insert_resource(GameRng::from_seed(0));
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        reseed_game_rng.before(spawn_level),
+    );
+}
+
+/// The seed the current run is played with. Picked along with the [`GameMode`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct RunSeed(pub u64);
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+impl RunSeed {
+    /// `day` only matters for the daily challenge, which everyone plays with
+    /// the same seed on the same day.
+    pub fn for_mode(game_mode: GameMode, day: u64) -> Self {
+        match game_mode {
+            GameMode::Daily => Self(daily::seed_for_day(day)),
+            GameMode::Endless
+            | GameMode::TimeAttack
+            | GameMode::Survival
+            | GameMode::Puzzle
+            | GameMode::Versus => Self::default(),
+        }
+    }
+}
+
+/// Mixed into the seed so each stream gets its own sequence.
+const ENEMY_PATHS_STREAM: u64 = 0x5eed_e4e3_1e5a_7a75;
+const GARBAGE_STREAM: u64 = 0x6a4b_a6e0_5eed_0b0b;
+
+/// Separate streams so e.g. shooting more doesn't change where later enemies go.
+#[derive(Resource)]
+pub struct GameRng {
+    pub layout: StdRng,
+    pub enemy_paths: StdRng,
+    /// Paths for garbage enemies sent over in versus.
+    pub garbage: StdRng,
+}
+
+impl GameRng {
+    fn from_seed(seed: u64) -> Self {
+        Self {
+            layout: StdRng::seed_from_u64(seed),
+            enemy_paths: StdRng::seed_from_u64(seed ^ ENEMY_PATHS_STREAM),
+            garbage: StdRng::seed_from_u64(seed ^ GARBAGE_STREAM),
+        }
+    }
+}
+
+fn reseed_game_rng(run_seed: Res<RunSeed>, mut game_rng: ResMut<GameRng>) {
+    *game_rng = GameRng::from_seed(run_seed.0);
+}
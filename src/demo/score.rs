@@ -3,22 +3,45 @@ use bevy::{
     prelude::*,
 };
 
+use super::{
+    daily::RuleModifiers,
+    hud::hud_frame,
+    player::{MAX_PLAYERS, player_color},
+};
 use crate::{AppSystems, PausableSystems, asset_tracking::LoadResource, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<ScoreController>();
+    app.register_type::<PlayerScoreVal>();
+    app.register_type::<ComboTimerBar>();
+    app.register_type::<ScoreCounter>();
+    app.register_type::<ComboPulse>();
+    app.register_type::<ScorePopup>();
     app.register_type::<ScoreUIAssets>();
     app.load_resource::<ScoreUIAssets>();
 
     app.init_resource::<Score>();
+    app.init_resource::<PlayerScores>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_player_scores);
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (update_combo_timer)
             .chain()
             .in_set(AppSystems::TickTimers)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        Update,
+        (
+            update_combo_timer_bar,
+            count_up_scores,
+            animate_combo_pulse,
+            update_score_popups.in_set(PausableSystems),
+        )
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
 }
 
 #[derive(Resource)]
@@ -30,42 +53,48 @@ impl FromWorld for Score {
     }
 }
 
+/// Each player's share of the score in co-op. Points from chains nobody set
+/// off only count towards the team [`Score`].
+#[derive(Resource, Default)]
+pub struct PlayerScores(pub [u32; MAX_PLAYERS]);
+
+fn reset_player_scores(mut player_scores: ResMut<PlayerScores>) {
+    player_scores.0 = [0; MAX_PLAYERS];
+}
+
 pub fn score_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
-    let slicer = TextureSlicer {
-        border: BorderRect::all(5.0),
-        center_scale_mode: SliceScaleMode::Stretch,
-        sides_scale_mode: SliceScaleMode::Stretch,
-        max_corner_scale: 1.0,
-    };
     (
-        Node {
-            position_type: PositionType::Absolute,
-            display: Display::Flex,
-            flex_direction: FlexDirection::Column,
-            bottom: Val::Px(5.0),
-            left: Val::Px(10.0),
-            padding: UiRect::all(Val::Px(20.0)),
-            ..default()
-        },
-        ImageNode {
-            image: score_ui_assets.frame.clone(),
-            image_mode: NodeImageMode::Sliced(slicer),
-            ..default()
-        },
+        hud_frame(
+            score_ui_assets,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(5.0),
+                left: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+        ),
         GlobalZIndex(-1),
         Transform::from_translation(Vec2::ZERO.extend(-9.0)),
         children![
             (Text::new("Score"), TextFont::from_font_size(18.0),),
-            (ScoreVal, Text::new("0"), TextFont::from_font_size(24.0),)
+            (
+                ScoreVal,
+                ScoreCounter::default(),
+                Text::new("0"),
+                TextFont::from_font_size(24.0),
+            )
         ],
         Observer::new(
             |trigger: Trigger<NewScore>,
-             mut score_text: Query<&mut Text, With<ScoreVal>>,
-             mut score_res: ResMut<Score>| {
-                for mut text in score_text.iter_mut() {
-                    text.0 = format!("{}", trigger.score);
+             mut score_counters: Query<&mut ScoreCounter, With<ScoreVal>>,
+             mut score_res: ResMut<Score>,
+             mut player_scores_res: ResMut<PlayerScores>| {
+                for mut counter in score_counters.iter_mut() {
+                    counter.target = trigger.score;
                 }
                 score_res.0 = trigger.score;
+                player_scores_res.0 = trigger.player_scores;
             },
         ),
         StateScoped(Screen::Gameplay),
@@ -73,36 +102,57 @@ pub fn score_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
 }
 
 pub fn combo_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
-    let slicer = TextureSlicer {
-        border: BorderRect::all(5.0),
-        center_scale_mode: SliceScaleMode::Stretch,
-        sides_scale_mode: SliceScaleMode::Stretch,
-        max_corner_scale: 1.0,
-    };
-
     (
-        Node {
-            position_type: PositionType::Absolute,
-            display: Display::Flex,
-            flex_direction: FlexDirection::Column,
-            align_items: AlignItems::End,
-            bottom: Val::Px(5.0),
-            right: Val::Px(10.0),
-            padding: UiRect::all(Val::Px(20.0)),
-            ..default()
-        },
-        ImageNode {
-            image: score_ui_assets.frame.clone(),
-            image_mode: NodeImageMode::Sliced(slicer),
-            ..default()
-        },
+        hud_frame(
+            score_ui_assets,
+            Node {
+                position_type: PositionType::Absolute,
+                align_items: AlignItems::End,
+                bottom: Val::Px(5.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+        ),
         children![
             (Text::new("combo"), TextFont::from_font_size(18.0),),
-            (ComboVal, Text::new("1x"), TextFont::from_font_size(24.0),)
+            (
+                ComboVal,
+                ComboPulse::default(),
+                Text::new("1x"),
+                TextFont::from_font_size(24.0),
+            ),
+            (
+                Name::new("Combo Timer Track"),
+                Node {
+                    width: Val::Px(COMBO_TIMER_BAR_WIDTH),
+                    height: Val::Px(COMBO_TIMER_BAR_HEIGHT),
+                    margin: UiRect::top(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.2)),
+                children![(
+                    ComboTimerBar,
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                )],
+            )
         ],
         Observer::new(
-            |trigger: Trigger<NewScore>, mut combo_text: Query<&mut Text, With<ComboVal>>| {
-                for mut text in combo_text.iter_mut() {
+            |trigger: Trigger<NewScore>,
+             mut combo_text: Query<(&mut Text, &mut ComboPulse), With<ComboVal>>| {
+                for (mut text, mut pulse) in combo_text.iter_mut() {
+                    if trigger.combo == pulse.combo {
+                        continue;
+                    }
+                    if trigger.combo > pulse.combo.max(1) {
+                        pulse.remaining = COMBO_PULSE_SECS;
+                    }
+                    pulse.combo = trigger.combo;
                     text.0 = format!("{}x", trigger.combo);
                 }
             },
@@ -111,37 +161,200 @@ pub fn combo_ui(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
     )
 }
 
+/// A player's own score in co-op, in their colour. Player one's sits top
+/// left and player two's top right.
+pub fn player_score_ui(index: usize, score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    let (left, right) = if index == 0 {
+        (Val::Px(10.0), Val::Auto)
+    } else {
+        (Val::Auto, Val::Px(10.0))
+    };
+    (
+        hud_frame(
+            score_ui_assets,
+            Node {
+                position_type: PositionType::Absolute,
+                align_items: AlignItems::Center,
+                top: Val::Px(5.0),
+                left,
+                right,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+        ),
+        children![
+            (
+                Text::new(format!("P{}", index + 1)),
+                TextFont::from_font_size(18.0),
+                TextColor(player_color(index)),
+            ),
+            (
+                PlayerScoreVal(index),
+                ScoreCounter::default(),
+                Text::new("0"),
+                TextFont::from_font_size(24.0),
+            )
+        ],
+        Observer::new(
+            move |trigger: Trigger<NewScore>,
+                  mut score_counters: Query<(&mut ScoreCounter, &PlayerScoreVal)>| {
+                for (mut counter, score_val) in score_counters.iter_mut() {
+                    if score_val.0 == index {
+                        counter.target = trigger.player_scores[index];
+                    }
+                }
+            },
+        ),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ScoreVal;
 
+/// Which player's score this text shows.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerScoreVal(pub usize);
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ComboVal;
 
-pub fn score_controller() -> impl Bundle {
+/// Counts a score text up towards the real score, rather than snapping to it.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct ScoreCounter {
+    shown: f32,
+    target: u32,
+}
+
+/// Fraction of the gap to the real score closed each second...
+const COUNT_UP_RATE: f32 = 8.0;
+/// ...but never slower than this many points a second, so it doesn't crawl
+/// the last few points.
+const MIN_COUNT_UP_SPEED: f32 = 20.0;
+
+fn count_up_scores(time: Res<Time>, mut counter_query: Query<(&mut ScoreCounter, &mut Text)>) {
+    let dt = time.delta_secs();
+    for (mut counter, mut text) in &mut counter_query {
+        let target = counter.target as f32;
+        if counter.shown == target {
+            continue;
+        }
+        let gap = target - counter.shown;
+        let step = (gap.abs() * (1.0 - (-COUNT_UP_RATE * dt).exp())).max(MIN_COUNT_UP_SPEED * dt);
+        counter.shown = if step >= gap.abs() {
+            target
+        } else {
+            counter.shown + step * gap.signum()
+        };
+        let shown = format!("{}", counter.shown as u32);
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+}
+
+/// Makes the combo text swell and flash whenever the combo goes up.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct ComboPulse {
+    combo: u32,
+    remaining: f32,
+}
+
+const COMBO_PULSE_SECS: f32 = 0.3;
+const COMBO_PULSE_SCALE: f32 = 0.6;
+const COMBO_PULSE_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+fn animate_combo_pulse(
+    time: Res<Time<Real>>,
+    mut pulse_query: Query<(&mut ComboPulse, &mut Transform, &mut TextColor)>,
+) {
+    for (mut pulse, mut transform, mut color) in &mut pulse_query {
+        if pulse.remaining <= 0.0 && transform.scale == Vec3::ONE {
+            continue;
+        }
+        pulse.remaining = (pulse.remaining - time.delta_secs()).max(0.0);
+        // quick swell that eases back down
+        let t = (pulse.remaining / COMBO_PULSE_SECS).powi(2);
+        transform.scale = Vec3::splat(1.0 + COMBO_PULSE_SCALE * t);
+        color.0 = Color::WHITE.mix(&COMBO_PULSE_COLOR, t);
+    }
+}
+
+const COMBO_TIMER_BAR_WIDTH: f32 = 60.0;
+const COMBO_TIMER_BAR_HEIGHT: f32 = 6.0;
+
+/// Drains over the combo window, so you can see how long you've got to keep a
+/// combo going. Empty when there's no combo to keep.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ComboTimerBar;
+
+fn update_combo_timer_bar(
+    score_controller_query: Query<&ScoreController>,
+    mut bar_query: Query<&mut Node, With<ComboTimerBar>>,
+) {
+    let Some(controller) = score_controller_query.iter().next() else {
+        return;
+    };
+    let combo = controller.top_combo();
+    let remaining = if combo.count > 1 {
+        1.0 - (combo.time_since_last_score / controller.combo_window).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    for mut node in bar_query.iter_mut() {
+        node.width = Val::Percent(100.0 * remaining);
+    }
+}
+
+/// `separate_combos` gives each player a combo of their own, for versus.
+pub fn score_controller(separate_combos: bool) -> impl Bundle {
     (
         ScoreController {
             score: 0,
-            combo: 0,
-            time_since_last_score: 0.0,
+            player_scores: [0; MAX_PLAYERS],
+            combos: [Combo::default(); MAX_PLAYERS],
+            separate_combos,
             combo_window: 1.0,
         },
         Observer::new(
             |trigger: Trigger<ScoreEvent>,
              mut score_controller: Query<&mut ScoreController>,
+             modifiers: Res<RuleModifiers>,
              mut commands: Commands| {
                 for mut controller in score_controller.iter_mut() {
-                    controller.score += trigger.score * controller.combo;
-                    if controller.time_since_last_score < controller.combo_window {
-                        controller.combo += 1;
-                    } else {
-                        controller.combo = 1;
+                    let combo_window = controller.combo_window;
+                    // chains nobody owns don't build up anyone's combo in versus
+                    let combo = match controller.combo_for(trigger.player) {
+                        Some(combo) => {
+                            let multiplier = combo.count;
+                            if combo.time_since_last_score < combo_window && !modifiers.no_combos {
+                                combo.count += 1;
+                            } else {
+                                combo.count = 1;
+                            }
+                            combo.time_since_last_score = 0.0;
+                            multiplier
+                        }
+                        None => 1,
+                    };
+                    let points = trigger.score * combo;
+                    controller.score += points;
+                    if let Some(player) = trigger.player {
+                        controller.player_scores[player] += points;
+                    }
+                    if points > 0 {
+                        commands.spawn(score_popup(points, combo, trigger.position));
                     }
-                    controller.time_since_last_score = 0.0;
                     commands.trigger(NewScore {
                         score: controller.score,
-                        combo: controller.combo,
+                        player_scores: controller.player_scores,
+                        combo: controller.combo(),
                     });
                 }
             },
@@ -149,27 +362,71 @@ pub fn score_controller() -> impl Bundle {
     )
 }
 
+/// Scores that come quickly enough after each other multiply.
+#[derive(Debug, Clone, Copy, Default, Reflect)]
+pub struct Combo {
+    pub count: u32,
+    pub time_since_last_score: f32,
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ScoreController {
     pub score: u32,
-    pub combo: u32,
-    pub time_since_last_score: f32,
+    pub player_scores: [u32; MAX_PLAYERS],
+    /// Solo and co-op only use the first, so either player can keep the
+    /// combo going. Versus gives each player their own, so one side's chain
+    /// doesn't multiply the other side's points.
+    pub combos: [Combo; MAX_PLAYERS],
+    pub separate_combos: bool,
     pub combo_window: f32,
 }
 
+impl ScoreController {
+    /// The biggest combo going, for the combo box and for effects that grow
+    /// with the chain.
+    pub fn combo(&self) -> u32 {
+        self.top_combo().count
+    }
+
+    fn top_combo(&self) -> Combo {
+        self.combos
+            .iter()
+            .copied()
+            .max_by_key(|combo| combo.count)
+            .unwrap_or_default()
+    }
+
+    /// The combo points scored by `player` count towards, if any.
+    fn combo_for(&mut self, player: Option<usize>) -> Option<&mut Combo> {
+        match (self.separate_combos, player) {
+            (false, _) => Some(&mut self.combos[0]),
+            (true, Some(player)) => Some(&mut self.combos[player]),
+            (true, None) => None,
+        }
+    }
+}
+
 fn update_combo_timer(
     mut query: Query<&mut ScoreController>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
     for mut controller in query.iter_mut() {
-        controller.time_since_last_score += time.delta_secs();
-        if controller.time_since_last_score > controller.combo_window {
-            controller.combo = 1;
+        let combo_window = controller.combo_window;
+        let mut combo_ended = false;
+        for combo in controller.combos.iter_mut() {
+            combo.time_since_last_score += time.delta_secs();
+            if combo.time_since_last_score > combo_window && combo.count > 1 {
+                combo.count = 1;
+                combo_ended = true;
+            }
+        }
+        if combo_ended {
             commands.trigger(NewScore {
                 score: controller.score,
-                combo: controller.combo,
+                player_scores: controller.player_scores,
+                combo: controller.combo(),
             });
         }
     }
@@ -178,19 +435,75 @@ fn update_combo_timer(
 #[derive(Event)]
 pub struct ScoreEvent {
     pub score: u32,
+    /// The player whose shot started the chain, if any.
+    pub player: Option<usize>,
+    /// Where the points were scored, for the popup.
+    pub position: Vec2,
+}
+
+const SCORE_POPUP_FONT_SIZE: f32 = 14.0;
+const SCORE_POPUP_Z: f32 = 7.0;
+const SCORE_POPUP_SECS: f32 = 0.8;
+const SCORE_POPUP_RISE_SPEED: f32 = 40.0;
+/// Popups start this much bigger and shrink to their normal size.
+const SCORE_POPUP_POP_SCALE: f32 = 0.5;
+
+/// Points floating up from where they were scored.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct ScorePopup {
+    remaining: f32,
+}
+
+fn score_popup(points: u32, combo: u32, position: Vec2) -> impl Bundle {
+    let text = if combo > 1 {
+        format!("+{points} x{combo}")
+    } else {
+        format!("+{points}")
+    };
+    (
+        Name::new("Score Popup"),
+        Text2d::new(text),
+        TextFont::from_font_size(SCORE_POPUP_FONT_SIZE),
+        TextColor(COMBO_PULSE_COLOR),
+        Transform::from_translation(position.extend(SCORE_POPUP_Z)),
+        ScorePopup {
+            remaining: SCORE_POPUP_SECS,
+        },
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn update_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut transform, mut color) in &mut popup_query {
+        popup.remaining -= time.delta_secs();
+        if popup.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = popup.remaining / SCORE_POPUP_SECS;
+        transform.translation.y += SCORE_POPUP_RISE_SPEED * time.delta_secs();
+        transform.scale = Vec3::splat(1.0 + SCORE_POPUP_POP_SCALE * t.powi(4));
+        color.0.set_alpha(t.min(0.5) * 2.0);
+    }
 }
 
 #[derive(Event)]
 pub struct NewScore {
     pub combo: u32,
     pub score: u32,
+    pub player_scores: [u32; MAX_PLAYERS],
 }
 
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct ScoreUIAssets {
     #[dependency]
-    frame: Handle<Image>,
+    pub frame: Handle<Image>,
 }
 
 impl FromWorld for ScoreUIAssets {
@@ -2,13 +2,18 @@ use bevy::prelude::*;
 
 use crate::{AppSystems, PausableSystems};
 
-use super::shot::{ShotAssets, shot};
+use super::{
+    explosions::ChainOwner,
+    mode::RunClock,
+    player::Player,
+    shot::{ShotAssets, ShotScale, shot},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<ShootingController>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (shoot_if_we_shooting)
             .chain()
             .run_if(resource_exists::<ShotAssets>)
@@ -24,7 +29,10 @@ pub struct ShootingController {
     pub last_shot_time: f32,
     pub reload_time: f32,
     pub shot_speed: f32,
+    pub shot_scale: ShotScale,
     pub target_offset: Vec2,
+    /// Shots remaining, if ammo is limited (e.g. in puzzle mode).
+    pub shots_left: Option<u32>,
 }
 
 impl Default for ShootingController {
@@ -34,29 +42,40 @@ impl Default for ShootingController {
             last_shot_time: 0.0,
             reload_time: 0.4,
             shot_speed: 680.0,
+            shot_scale: ShotScale::default(),
             target_offset: Vec2 { x: 0.0, y: 200.0 },
+            shots_left: None,
         }
     }
 }
 
-fn shoot_if_we_shooting(
-    time: Res<Time>,
-    mut shooting_query: Query<(&mut ShootingController, &Transform)>,
+pub(super) fn shoot_if_we_shooting(
+    run_clock: Res<RunClock>,
+    mut shooting_query: Query<(&mut ShootingController, &Transform, Option<&Player>)>,
     mut commands: Commands,
     shot_assets: Res<ShotAssets>,
 ) {
-    for (mut controller, transform) in &mut shooting_query {
+    for (mut controller, transform, player) in &mut shooting_query {
+        let has_ammo = controller.shots_left.is_none_or(|shots| shots > 0);
         if controller.intent_to_fire
-            && time.elapsed_secs() - controller.last_shot_time > controller.reload_time
+            && has_ammo
+            && run_clock.0 - controller.last_shot_time > controller.reload_time
         {
-            controller.last_shot_time = time.elapsed_secs();
+            controller.last_shot_time = run_clock.0;
+            if let Some(shots) = controller.shots_left.as_mut() {
+                *shots -= 1;
+            }
             // todo: use transform and pass to new shot, also grab controller offset amount for target_offset
-            commands.spawn(shot(
+            let mut shot_entity = commands.spawn(shot(
                 controller.shot_speed,
+                controller.shot_scale,
                 &shot_assets,
-                Some(transform.translation.truncate() + controller.target_offset),
+                transform.translation.truncate() + controller.target_offset,
                 transform.translation.truncate(),
             ));
+            if let Some(player) = player {
+                shot_entity.insert(ChainOwner(player.index));
+            }
         }
     }
 }
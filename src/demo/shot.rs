@@ -3,9 +3,14 @@ use bevy::{
     prelude::*,
 };
 
-use super::explosions::{ExplosionAssets, explosion};
+use super::daily::RuleModifiers;
+use super::explosions::{ChainOwner, ExplosionAssets, ShotExplosion, explosion};
+use super::simulation::Interpolated;
 use crate::{
-    AppSystems, PausableSystems, asset_tracking::LoadResource, audio::sound_effect, screens::Screen,
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    audio::{PlaySfx, SfxCategory},
+    screens::{Screen, in_play},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -15,7 +20,7 @@ pub(super) fn plugin(app: &mut App) {
     app.load_resource::<ShotAssets>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (update_shot_position, shot_end_of_life)
             .chain()
             .run_if(resource_exists::<ExplosionAssets>)
@@ -23,36 +28,97 @@ pub(super) fn plugin(app: &mut App) {
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        Update,
+        (
+            play_shot_sounds.run_if(resource_exists::<ShotAssets>),
+            draw_shot_trails,
+        )
+            .run_if(in_play)
+            .in_set(AppSystems::Update),
+    );
 }
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+/// A shot flying from `initial_location` to `target`. Where it is only
+/// depends on how long it's been flying, so it lands exactly on time no
+/// matter how the ticks fall.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
 #[reflect(Component)]
-struct Shot {
-    pub speed: f32,
-    pub target: Option<Vec2>,
+pub struct Shot {
     pub initial_location: Vec2,
+    pub target: Vec2,
+    /// Seconds from leaving the cannon to landing.
+    pub flight_time: f32,
+    pub elapsed: f32,
+    pub scale: ShotScale,
+}
+
+impl Shot {
+    /// How far through its flight the shot is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.flight_time <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.flight_time).min(1.0)
+        }
+    }
+
+    pub fn has_landed(&self) -> bool {
+        self.progress() >= 1.0
+    }
+}
+
+/// How a shot shrinks on its way to the target, so it looks like it's
+/// arcing away from the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct ShotScale {
+    pub start: f32,
+    pub end: f32,
+    pub easing: EaseFunction,
+}
+
+impl Default for ShotScale {
+    fn default() -> Self {
+        Self {
+            start: 2.0,
+            end: 0.5,
+            // shrinks slowly at first, then quickly as it comes down
+            easing: EaseFunction::QuadraticIn,
+        }
+    }
+}
+
+impl ShotScale {
+    fn at(&self, progress: f32) -> f32 {
+        self.start
+            .lerp(self.end, self.easing.sample_clamped(progress))
+    }
 }
 
 pub fn shot(
     speed: f32,
+    scale: ShotScale,
     shot_assets: &ShotAssets,
-    target: Option<Vec2>,
+    target: Vec2,
     initial_location: Vec2,
 ) -> impl Bundle {
     (
         Name::new("Shot"),
         Shot {
-            speed,
-            target,
             initial_location,
+            target,
+            flight_time: initial_location.distance(target) / speed,
+            elapsed: 0.0,
+            scale,
         },
-        Transform::from_translation(initial_location.extend(0.0)),
+        Transform::from_translation(initial_location.extend(0.0))
+            .with_scale(Vec3::splat(scale.at(0.0))),
+        Interpolated::default(),
         Sprite {
             image: shot_assets.texture.clone(),
             ..default()
         },
         StateScoped(Screen::Gameplay),
-        children![sound_effect(shot_assets.bang.clone()),],
     )
 }
 
@@ -81,58 +147,60 @@ impl FromWorld for ShotAssets {
     }
 }
 
-const SLOWDOWN_THRESHOLD_DISTANCE: f32 = 10.0;
-const SLOWDOWN_FACTOR: f32 = 0.5;
+fn play_shot_sounds(
+    shot_query: Query<&Transform, Added<Shot>>,
+    shot_assets: Res<ShotAssets>,
+    mut ev_play_sfx: EventWriter<PlaySfx>,
+) {
+    for transform in &shot_query {
+        ev_play_sfx.write(
+            PlaySfx::new(shot_assets.bang.clone(), SfxCategory::Shot)
+                .at(transform.translation.truncate()),
+        );
+    }
+}
 
 fn update_shot_position(mut query: Query<(&mut Transform, &mut Shot)>, time: Res<Time>) {
     for (mut transform, mut shot) in query.iter_mut() {
-        if let Some(target) = shot.target {
-            let direction = target - transform.translation.xy();
-            let distance = direction.length();
-            if distance < 0.1 {
-                // Shot has reached its target
-                shot.target = None;
-            } else {
-                let direction = direction.normalize();
-                shot.speed = if distance < SLOWDOWN_THRESHOLD_DISTANCE {
-                    shot.speed * SLOWDOWN_FACTOR
-                } else {
-                    shot.speed
-                };
-                transform.translation.x += direction.x * shot.speed * time.delta_secs();
-                transform.translation.y += direction.y * shot.speed * time.delta_secs();
-                // modify scale based on distance from initial location
-                let initial_target_distance = shot.initial_location.distance(target);
-                let percent_travelled =
-                    (initial_target_distance - distance) / initial_target_distance;
-                // use a quadratic function to scale the shot, with a desired starting scale and ending scale
-                // TODO: maybe cubic would look better with the peak scale happening a little after the initial location
-                let initial_scale = 2.0;
-                let final_scale = 0.5;
-                let scale_factor =
-                    initial_scale + (final_scale - initial_scale) * percent_travelled.powi(2);
-                transform.scale = Vec3::splat(scale_factor);
-            }
-        }
+        shot.elapsed += time.delta_secs();
+        let progress = shot.progress();
+        let position = shot.initial_location.lerp(shot.target, progress);
+        transform.translation = position.extend(transform.translation.z);
+        transform.scale = Vec3::splat(shot.scale.at(progress));
+    }
+}
+
+const TRAIL_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+
+/// A faint line from where each shot was fired to where it'll land.
+fn draw_shot_trails(mut gizmos: Gizmos, query: Query<&Shot>) {
+    for shot in &query {
+        gizmos.line_2d(shot.initial_location, shot.target, TRAIL_COLOR);
     }
 }
 
 fn shot_end_of_life(
-    query: Query<(&Shot, &Transform, Entity)>,
+    query: Query<(&Shot, &Transform, Option<&ChainOwner>, Entity)>,
     mut commands: Commands,
     explosion_assets: Res<ExplosionAssets>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    modifiers: Res<RuleModifiers>,
 ) {
-    for (shot, transform, entity) in query.iter() {
-        if shot.target.is_none() {
-            // Shot has reached its target
-            commands.spawn(explosion(
-                transform.translation.truncate(),
-                16.0,
-                1.0,
-                &explosion_assets,
-                &mut texture_atlas_layouts,
+    for (shot, transform, chain_owner, entity) in query.iter() {
+        if shot.has_landed() {
+            let mut explosion_entity = commands.spawn((
+                explosion(
+                    transform.translation.truncate(),
+                    16.0 * modifiers.explosion_radius_multiplier(),
+                    1.0,
+                    &explosion_assets,
+                    &mut texture_atlas_layouts,
+                ),
+                ShotExplosion::default(),
             ));
+            if let Some(chain_owner) = chain_owner {
+                explosion_entity.insert(*chain_owner);
+            }
             commands.entity(entity).despawn();
         }
     }
@@ -0,0 +1,87 @@
+//! Gameplay runs in `FixedUpdate`, a set number of ticks per second no matter
+//! the frame rate, so a slow frame can't make shots overshoot or let
+//! explosions skip past what they should have hit. Things that move are drawn
+//! partway between their last two ticks, so they still look smooth when the
+//! frame rate and tick rate don't line up.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TickRate>();
+    app.register_type::<Interpolated>();
+    app.init_resource::<TickRate>();
+
+    app.add_systems(Update, apply_tick_rate.run_if(resource_changed::<TickRate>));
+
+    app.add_observer(start_interpolating);
+    app.add_systems(FixedFirst, restore_simulated_translation);
+    app.add_systems(FixedLast, record_simulated_translation);
+    app.add_systems(
+        RunFixedMainLoop,
+        interpolate_translation.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+    );
+}
+
+/// The tick rates offered in the settings menu.
+pub const TICK_RATES: [u32; 3] = [30, 60, 120];
+
+/// Gameplay ticks per second.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct TickRate(pub u32);
+
+impl Default for TickRate {
+    fn default() -> Self {
+        Self(60)
+    }
+}
+
+fn apply_tick_rate(tick_rate: Res<TickRate>, mut time: ResMut<Time<Fixed>>) {
+    time.set_timestep_hz(tick_rate.0.into());
+}
+
+/// Draws the entity between where it was on the last two ticks, instead of
+/// jumping once per tick. Gameplay only ever sees the ticked position.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+fn start_interpolating(
+    trigger: Trigger<OnAdd, Interpolated>,
+    mut query: Query<(&Transform, &mut Interpolated)>,
+) {
+    if let Ok((transform, mut interpolated)) = query.get_mut(trigger.target()) {
+        interpolated.previous = transform.translation;
+        interpolated.current = transform.translation;
+    }
+}
+
+/// Puts back the ticked position before gameplay runs, undoing whatever was
+/// drawn last frame.
+fn restore_simulated_translation(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in &mut query {
+        transform.translation = interpolated.current;
+    }
+}
+
+/// Runs even while paused, so a paused game settles on its last tick rather
+/// than wobbling between the last two.
+fn record_simulated_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.previous = interpolated.current;
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translation(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let overstep = fixed_time.overstep_fraction();
+    for (mut transform, interpolated) in &mut query {
+        transform.translation = interpolated.previous.lerp(interpolated.current, overstep);
+    }
+}
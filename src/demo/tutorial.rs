@@ -0,0 +1,339 @@
+//! The tutorial: a quiet field with no waves, where each step waits for the
+//! player to try something before moving on. It runs on its own the first
+//! time someone starts a run, and can be replayed from the main menu.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use super::{
+    enemies::{ENEMY_EXPLOSION_RADIUS, EnemyAssets, stationary_enemy},
+    explosions::Explosion,
+    field::{PlayArea, PlayField},
+    floating_text::NewText,
+    hud::hud_frame,
+    player::{Player, PlayerAssets, player},
+    reticle::{ReticleAssets, reticle},
+    score::{ScoreEvent, ScoreUIAssets},
+    shooting::ShootingController,
+    shot::Shot,
+};
+use crate::{
+    AppSystems,
+    background::{BGAssets, Backdrop, backdrop, play_field_frame},
+    save::SaveData,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Tutorial>();
+    app.register_type::<TutorialEnemy>();
+    app.register_type::<TutorialPrompt>();
+    app.init_resource::<Tutorial>();
+
+    app.add_observer(count_tutorial_links);
+    app.add_systems(OnEnter(Screen::Tutorial), spawn_tutorial);
+    app.add_systems(
+        Update,
+        (
+            advance_tutorial,
+            update_tutorial_prompt.run_if(resource_changed::<Tutorial>),
+            finish_tutorial.run_if(tutorial_over.or(input_just_pressed(KeyCode::Escape))),
+        )
+            .chain()
+            .run_if(in_state(Screen::Tutorial))
+            .in_set(AppSystems::Update),
+    );
+    app.add_systems(OnExit(Screen::Tutorial), clean_up_tutorial);
+}
+
+/// Where to go once the tutorial is over. Inserted by whatever sends the
+/// player to the tutorial, and removed again when they leave it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AfterTutorial(pub Screen);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+enum TutorialStep {
+    #[default]
+    Move,
+    Aim,
+    Fire,
+    Chain,
+    Done,
+}
+
+impl TutorialStep {
+    fn prompt(self) -> &'static str {
+        match self {
+            Self::Move => "Move your cannon with A / D or Left / Right",
+            Self::Aim => "Raise and lower your aim with W / S or Up / Down",
+            Self::Fire => "Fire at the enemy with Space",
+            Self::Chain => "Explosions set off any enemy they touch.\nHit one to blow up the row",
+            Self::Done => "Chain them up to keep the buildings safe. Good luck!",
+        }
+    }
+}
+
+/// How far along the player is, and where they were when the current step
+/// started.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+struct Tutorial {
+    step: TutorialStep,
+    start_x: Option<f32>,
+    start_aim: Option<f32>,
+    /// Enemies set off since the last shot finished exploding.
+    links: u32,
+    /// Seconds left showing the final message.
+    done_timer: f32,
+}
+
+impl Tutorial {
+    fn go_to(&mut self, step: TutorialStep) {
+        *self = Self {
+            step,
+            done_timer: DONE_SECS,
+            ..default()
+        };
+    }
+}
+
+/// How far the cannon has to move, and the reticle has to go up or down, to
+/// show the player has got the hang of it.
+const MOVE_DISTANCE: f32 = 150.0;
+const AIM_DISTANCE: f32 = 100.0;
+/// Enemies that have to go off from a single shot to pass the chain step.
+const CHAIN_GOAL: u32 = 3;
+const CHAIN_ROW_LENGTH: usize = 5;
+/// Far enough apart that only neighbours set each other off.
+const CHAIN_SPACING: f32 = 55.0;
+const DONE_SECS: f32 = 3.0;
+
+/// Marks the enemies the tutorial sets up, so it can tell when they're gone.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct TutorialEnemy;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct TutorialPrompt;
+
+fn spawn_tutorial(
+    mut commands: Commands,
+    player_assets: Res<PlayerAssets>,
+    reticle_assets: Res<ReticleAssets>,
+    bg_assets: Res<BGAssets>,
+    score_ui_assets: Res<ScoreUIAssets>,
+    play_area: Res<PlayArea>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    tutorial.go_to(TutorialStep::default());
+
+    let level = commands
+        .spawn((
+            Name::new("Tutorial"),
+            Transform::default(),
+            Visibility::default(),
+            StateScoped(Screen::Tutorial),
+            children![
+                backdrop(Backdrop::Gameplay),
+                play_field_frame(&bg_assets, &play_area),
+            ],
+        ))
+        .id();
+    let cannon = commands
+        .spawn((
+            player(
+                0,
+                Vec2::new(0.0, play_area.player_y),
+                false,
+                500.0,
+                &player_assets,
+            ),
+            PlayField::whole(&play_area),
+            ChildOf(level),
+        ))
+        .id();
+    commands.spawn((
+        reticle(&reticle_assets, cannon, None),
+        Name::new("Reticle"),
+        ChildOf(level),
+    ));
+    commands.spawn(tutorial_callout(&score_ui_assets));
+}
+
+fn tutorial_callout(score_ui_assets: &ScoreUIAssets) -> impl Bundle {
+    (
+        Name::new("Tutorial Callout"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Tutorial),
+        children![(
+            hud_frame(
+                score_ui_assets,
+                Node {
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+            ),
+            children![
+                (Text::new("tutorial"), TextFont::from_font_size(18.0)),
+                (
+                    TutorialPrompt,
+                    Text::new(TutorialStep::default().prompt()),
+                    TextFont::from_font_size(22.0),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ),
+                (Text::new("Esc to skip"), TextFont::from_font_size(14.0)),
+            ],
+        )],
+    )
+}
+
+fn spawn_tutorial_enemy(commands: &mut Commands, position: Vec2, enemy_assets: &EnemyAssets) {
+    commands
+        .spawn((
+            stationary_enemy(position, ENEMY_EXPLOSION_RADIUS, enemy_assets),
+            TutorialEnemy,
+        ))
+        // swaps out the gameplay scope the enemy comes with
+        .insert(StateScoped(Screen::Tutorial));
+}
+
+fn spawn_target(
+    commands: &mut Commands,
+    enemy_assets: &EnemyAssets,
+    ev_new_text: &mut EventWriter<NewText>,
+) {
+    let position = Vec2::new(150.0, 80.0);
+    spawn_tutorial_enemy(commands, position, enemy_assets);
+    ev_new_text.write(NewText(
+        "shoot me!".to_string(),
+        position.x,
+        position.y + 30.0,
+    ));
+}
+
+fn spawn_chain_row(
+    commands: &mut Commands,
+    enemy_assets: &EnemyAssets,
+    ev_new_text: &mut EventWriter<NewText>,
+) {
+    let y = 120.0;
+    let half_length = (CHAIN_ROW_LENGTH - 1) as f32 / 2.0;
+    for index in 0..CHAIN_ROW_LENGTH {
+        let x = (index as f32 - half_length) * CHAIN_SPACING;
+        spawn_tutorial_enemy(commands, Vec2::new(x, y), enemy_assets);
+    }
+    ev_new_text.write(NewText("set off a chain!".to_string(), 0.0, y + 30.0));
+}
+
+fn count_tutorial_links(_: Trigger<ScoreEvent>, mut tutorial: ResMut<Tutorial>) {
+    tutorial.links += 1;
+}
+
+fn advance_tutorial(
+    mut commands: Commands,
+    player: Single<(&Transform, &ShootingController), With<Player>>,
+    enemy_query: Query<Entity, With<TutorialEnemy>>,
+    shot_query: Query<(), With<Shot>>,
+    explosion_query: Query<(), With<Explosion>>,
+    enemy_assets: Res<EnemyAssets>,
+    time: Res<Time>,
+    mut tutorial: ResMut<Tutorial>,
+    mut ev_new_text: EventWriter<NewText>,
+) {
+    let (transform, shooting_controller) = *player;
+    let x = transform.translation.x;
+    let aim = shooting_controller.target_offset.y;
+    // let the last explosions play out before judging a shot
+    let settled = shot_query.is_empty() && explosion_query.is_empty();
+
+    match tutorial.step {
+        TutorialStep::Move => {
+            let start_x = *tutorial.start_x.get_or_insert(x);
+            if (x - start_x).abs() >= MOVE_DISTANCE {
+                tutorial.go_to(TutorialStep::Aim);
+            }
+        }
+        TutorialStep::Aim => {
+            let start_aim = *tutorial.start_aim.get_or_insert(aim);
+            if (aim - start_aim).abs() >= AIM_DISTANCE {
+                tutorial.go_to(TutorialStep::Fire);
+                spawn_target(&mut commands, &enemy_assets, &mut ev_new_text);
+            }
+        }
+        TutorialStep::Fire => {
+            if settled && enemy_query.is_empty() {
+                tutorial.go_to(TutorialStep::Chain);
+                spawn_chain_row(&mut commands, &enemy_assets, &mut ev_new_text);
+            }
+        }
+        TutorialStep::Chain => {
+            if !settled || tutorial.links == 0 {
+                return;
+            }
+            if tutorial.links >= CHAIN_GOAL {
+                tutorial.go_to(TutorialStep::Done);
+                return;
+            }
+            tutorial.links = 0;
+            // not enough left to pass with, so start the row over
+            if enemy_query.iter().len() < CHAIN_GOAL as usize {
+                for enemy in &enemy_query {
+                    commands.entity(enemy).despawn();
+                }
+                spawn_chain_row(&mut commands, &enemy_assets, &mut ev_new_text);
+            }
+        }
+        TutorialStep::Done => {
+            tutorial.done_timer -= time.delta_secs();
+        }
+    }
+}
+
+fn update_tutorial_prompt(
+    tutorial: Res<Tutorial>,
+    mut prompt: Single<&mut Text, With<TutorialPrompt>>,
+) {
+    let text = tutorial.step.prompt();
+    // the resource changes every frame on some steps, so only touch the text
+    // when it's actually different
+    if prompt.0 != text {
+        prompt.0 = text.to_string();
+    }
+}
+
+fn tutorial_over(tutorial: Res<Tutorial>) -> bool {
+    tutorial.step == TutorialStep::Done && tutorial.done_timer <= 0.0
+}
+
+/// Finishing and skipping both count, so the tutorial won't start on its own
+/// again either way.
+fn finish_tutorial(
+    after_tutorial: Option<Res<AfterTutorial>>,
+    mut save_data: ResMut<SaveData>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if !save_data.tutorial_done {
+        save_data.tutorial_done = true;
+    }
+    next_screen.set(after_tutorial.map_or(Screen::Title, |after| after.0));
+}
+
+/// Shots, explosions and floating text are scoped to gameplay, which the
+/// tutorial never enters, so anything still around has to go by hand.
+fn clean_up_tutorial(mut commands: Commands, scoped_query: Query<(Entity, &StateScoped<Screen>)>) {
+    for (entity, scoped) in &scoped_query {
+        if scoped.0 == Screen::Gameplay {
+            commands.entity(entity).despawn();
+        }
+    }
+    commands.remove_resource::<AfterTutorial>();
+}
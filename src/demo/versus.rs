@@ -0,0 +1,189 @@
+//! Versus mode: two players on split fields. Big chains on one side send
+//! garbage enemies over to the other side, the way garbage blocks work in
+//! puzzle games. Whoever loses all their buildings first loses the match.
+
+use bevy::prelude::*;
+
+use super::{
+    buildings::{Building, Destroyed},
+    enemies::{EnemyAssets, EnemyController, garbage_enemy},
+    explosions::{ChainOwner, Explosion},
+    field::{PlayArea, PlayField},
+    floating_text::NewText,
+    mode::GameMode,
+    player::MAX_PLAYERS,
+    rng::GameRng,
+    score::ScoreEvent,
+};
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChainTally>();
+    app.init_resource::<ChainTally>();
+
+    app.add_observer(tally_chain_links);
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (
+            reset_versus,
+            spawn_field_divider.run_if(resource_equals(GameMode::Versus)),
+        ),
+    );
+    app.add_systems(
+        FixedUpdate,
+        (send_garbage, check_for_versus_end)
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_equals(GameMode::Versus)))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Chains need at least this many links to send anything over.
+const GARBAGE_THRESHOLD: u32 = 4;
+/// Every this many links past the threshold sends one more enemy.
+const LINKS_PER_EXTRA_GARBAGE: u32 = 3;
+const MAX_GARBAGE_PER_CHAIN: u32 = 6;
+
+fn garbage_for_chain(links: u32) -> u32 {
+    if links < GARBAGE_THRESHOLD {
+        0
+    } else {
+        (1 + (links - GARBAGE_THRESHOLD) / LINKS_PER_EXTRA_GARBAGE).min(MAX_GARBAGE_PER_CHAIN)
+    }
+}
+
+/// Links in each player's chain so far. Cashed in as garbage once the chain
+/// has finished exploding.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+struct ChainTally([u32; MAX_PLAYERS]);
+
+/// Who won the last versus match, or `None` if both sides fell at once.
+#[derive(Resource, Debug)]
+pub struct VersusResult {
+    pub winner: Option<usize>,
+}
+
+fn reset_versus(mut commands: Commands, mut tally: ResMut<ChainTally>) {
+    commands.remove_resource::<VersusResult>();
+    tally.0 = [0; MAX_PLAYERS];
+}
+
+const DIVIDER_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.3);
+
+fn spawn_field_divider(mut commands: Commands, play_area: Res<PlayArea>) {
+    commands.spawn((
+        Name::new("Field Divider"),
+        Sprite::from_color(DIVIDER_COLOR, Vec2::new(2.0, play_area.view_size.y)),
+        Transform::from_xyz(0.0, 0.0, -2.0),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn tally_chain_links(
+    trigger: Trigger<ScoreEvent>,
+    game_mode: Res<GameMode>,
+    mut tally: ResMut<ChainTally>,
+) {
+    if *game_mode != GameMode::Versus {
+        return;
+    }
+    if let Some(player) = trigger.player {
+        tally.0[player] += 1;
+    }
+}
+
+fn send_garbage(
+    mut commands: Commands,
+    mut tally: ResMut<ChainTally>,
+    explosion_query: Query<&ChainOwner, With<Explosion>>,
+    controller_query: Query<(&EnemyController, &PlayField)>,
+    enemy_assets: Res<EnemyAssets>,
+    mut game_rng: ResMut<GameRng>,
+    mut ev_new_text: EventWriter<NewText>,
+    play_area: Res<PlayArea>,
+) {
+    for player in 0..MAX_PLAYERS {
+        // wait for the chain to finish before cashing it in
+        let chain_going = explosion_query.iter().any(|owner| owner.0 == player);
+        if tally.0[player] == 0 || chain_going {
+            continue;
+        }
+        let garbage = garbage_for_chain(tally.0[player]);
+        tally.0[player] = 0;
+        let Some((controller, field)) = controller_query
+            .iter()
+            .find(|(_, field)| field.index != player)
+        else {
+            continue;
+        };
+        if garbage == 0 {
+            continue;
+        }
+        for _ in 0..garbage {
+            commands.spawn(garbage_enemy(
+                controller.enemy_speed,
+                *field,
+                &play_area,
+                &enemy_assets,
+                &mut game_rng.garbage,
+            ));
+        }
+        ev_new_text.write(NewText(
+            format!("+{garbage} incoming"),
+            field.center_x,
+            100.0,
+        ));
+    }
+}
+
+fn check_for_versus_end(
+    mut commands: Commands,
+    building_query: Query<&PlayField, (With<Building>, Without<Destroyed>)>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let standing = (0..MAX_PLAYERS)
+        .filter(|&player| building_query.iter().any(|field| field.index == player))
+        .collect::<Vec<_>>();
+    if standing.len() == MAX_PLAYERS {
+        return;
+    }
+    commands.insert_resource(VersusResult {
+        winner: match standing.as_slice() {
+            [winner] => Some(*winner),
+            _ => None,
+        },
+    });
+    next_screen.set(Screen::GameOver);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_chains_send_nothing() {
+        for links in 0..GARBAGE_THRESHOLD {
+            assert_eq!(garbage_for_chain(links), 0);
+        }
+    }
+
+    #[test]
+    fn longer_chains_send_more() {
+        assert_eq!(garbage_for_chain(GARBAGE_THRESHOLD), 1);
+        assert_eq!(
+            garbage_for_chain(GARBAGE_THRESHOLD + LINKS_PER_EXTRA_GARBAGE - 1),
+            1
+        );
+        assert_eq!(
+            garbage_for_chain(GARBAGE_THRESHOLD + LINKS_PER_EXTRA_GARBAGE),
+            2
+        );
+    }
+
+    #[test]
+    fn garbage_is_capped() {
+        assert_eq!(garbage_for_chain(u32::MAX), MAX_GARBAGE_PER_CHAIN);
+    }
+}
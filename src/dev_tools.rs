@@ -5,7 +5,7 @@ use bevy::{
     ui::UiDebugOptions,
 };
 
-use crate::screens::Screen;
+use crate::{demo::difficulty::AdaptiveDifficulty, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -16,6 +16,14 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
     );
+
+    // Show tuning values alongside the UI debug overlay.
+    app.register_type::<DebugOverlayText>();
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_debug_overlay);
+    app.add_systems(
+        Update,
+        update_debug_overlay.run_if(in_state(Screen::Gameplay)),
+    );
 }
 
 const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
@@ -23,3 +31,42 @@ const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DebugOverlayText;
+
+fn spawn_debug_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Debug Overlay"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Text::new(""),
+        TextFont::from_font_size(14.0),
+        Visibility::Hidden,
+        DebugOverlayText,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_debug_overlay(
+    options: Res<UiDebugOptions>,
+    difficulty: Res<AdaptiveDifficulty>,
+    mut overlay: Single<(&mut Text, &mut Visibility), With<DebugOverlayText>>,
+) {
+    let (text, visibility) = &mut *overlay;
+    **visibility = if options.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    text.0 = format!(
+        "difficulty adj: {:+.2} ({})",
+        difficulty.adjustment,
+        if difficulty.enabled { "on" } else { "off" }
+    );
+}
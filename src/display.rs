@@ -0,0 +1,158 @@
+//! Window mode, vsync and how the game is scaled to fit the window. Picked on
+//! the display settings page and kept in the save data.
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    render::camera::ScalingMode,
+    window::{PresentMode, PrimaryWindow, VideoModeSelection, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{demo::field::PlayArea, save::SaveData};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DisplaySettings>();
+    app.init_resource::<DisplaySettings>();
+
+    app.add_systems(Startup, load_display_settings);
+    app.add_systems(
+        Update,
+        (
+            toggle_fullscreen.run_if(input_just_pressed(KeyCode::F11)),
+            apply_window_settings.run_if(resource_changed::<DisplaySettings>),
+            save_display_settings.run_if(
+                resource_changed::<DisplaySettings>.and(not(resource_added::<DisplaySettings>)),
+            ),
+            fit_view_to_window,
+        )
+            .chain(),
+    );
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [Self; 3] = [Self::Windowed, Self::Borderless, Self::Fullscreen];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Windowed => "Windowed",
+            Self::Borderless => "Borderless",
+            Self::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            Self::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+            }
+        }
+    }
+}
+
+/// The largest pixel scale offered in the settings menu, if the window is big
+/// enough for it.
+pub const MAX_PIXEL_SCALE: u32 = 4;
+
+/// How the game is shown. New fields should have a sensible [`Default`] so
+/// older saves keep loading.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    pub vsync: bool,
+    /// Screen pixels per game pixel, so pixel art stays crisp. Zero scales
+    /// the play area to fill the window instead, whatever size that makes it.
+    pub pixel_scale: u32,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Windowed,
+            vsync: true,
+            pixel_scale: 0,
+        }
+    }
+}
+
+fn load_display_settings(save_data: Res<SaveData>, mut display: ResMut<DisplaySettings>) {
+    *display = save_data.display;
+}
+
+fn save_display_settings(display: Res<DisplaySettings>, mut save_data: ResMut<SaveData>) {
+    if save_data.display != *display {
+        save_data.display = *display;
+    }
+}
+
+fn toggle_fullscreen(mut display: ResMut<DisplaySettings>) {
+    display.mode = match display.mode {
+        DisplayMode::Windowed => DisplayMode::Borderless,
+        DisplayMode::Borderless | DisplayMode::Fullscreen => DisplayMode::Windowed,
+    };
+}
+
+fn apply_window_settings(
+    display: Res<DisplaySettings>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    window.mode = display.mode.window_mode();
+    window.present_mode = if display.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+/// How much the play area can be scaled up and still fit in the window.
+fn fit_scale(window: &Window, play_area: &PlayArea) -> f32 {
+    (window.width() / play_area.view_size.x).min(window.height() / play_area.view_size.y)
+}
+
+/// The biggest pixel scale that still shows the whole play area, so a scale
+/// picked on a big screen can't crop the view on a smaller one.
+pub fn max_pixel_scale(window: &Window, play_area: &PlayArea) -> u32 {
+    (fit_scale(window, play_area).floor() as u32).clamp(1, MAX_PIXEL_SCALE)
+}
+
+/// Scales the camera, and the UI along with it, so the whole play area fits
+/// in the window. Wider or taller windows see more of the backdrop.
+fn fit_view_to_window(
+    window: Single<&Window, With<PrimaryWindow>>,
+    display: Res<DisplaySettings>,
+    play_area: Res<PlayArea>,
+    mut projection: Single<&mut Projection, With<Camera2d>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let scale = match display.pixel_scale {
+        0 => fit_scale(&window, &play_area),
+        pixel_scale => pixel_scale.min(max_pixel_scale(&window, &play_area)) as f32,
+    };
+    if scale <= 0.0 {
+        return;
+    }
+    if let Projection::Orthographic(orthographic) = &**projection
+        && matches!(orthographic.scaling_mode, ScalingMode::WindowSize)
+        && orthographic.scale == 1.0 / scale
+    {
+        return;
+    }
+    **projection = Projection::Orthographic(OrthographicProjection {
+        scaling_mode: ScalingMode::WindowSize,
+        scale: 1.0 / scale,
+        ..OrthographicProjection::default_2d()
+    });
+    ui_scale.0 = scale;
+}
@@ -0,0 +1,4 @@
+//! The parts of the game that are useful outside it, like in the benchmarks.
+//! The game itself is the binary in `main.rs`.
+
+pub mod spatial;
@@ -9,13 +9,15 @@ mod background;
 mod demo;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod display;
 mod menus;
+pub mod net;
+pub mod save;
 mod screens;
 mod theme;
 
 use bevy::{
     asset::{AssetMetaCheck, load_internal_binary_asset},
-    audio::Volume,
     prelude::*,
     window::WindowResolution,
 };
@@ -42,7 +44,7 @@ impl Plugin for AppPlugin {
                     primary_window: Window {
                         title: "chainvasion".to_string(),
                         fit_canvas_to_parent: true,
-                        resizable: false,
+                        resizable: true,
                         resolution: WindowResolution::new(800., 640.),
                         ..default()
                     }
@@ -64,9 +66,12 @@ impl Plugin for AppPlugin {
             asset_tracking::plugin,
             audio::plugin,
             demo::plugin,
+            display::plugin,
             #[cfg(feature = "dev")]
             dev_tools::plugin,
             menus::plugin,
+            net::plugin,
+            save::plugin,
             screens::plugin,
             theme::plugin,
             background::plugin,
@@ -82,20 +87,29 @@ impl Plugin for AppPlugin {
             )
                 .chain(),
         );
+        // Gameplay simulation runs in `FixedUpdate`, with the same ordering.
+        app.configure_sets(
+            FixedUpdate,
+            (
+                AppSystems::TickTimers,
+                AppSystems::RecordInput,
+                AppSystems::Update,
+            )
+                .chain(),
+        );
 
         // Set up the `Pause` state.
         app.init_state::<Pause>();
         app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
-
-        // Set volume to 50%
-        app.add_systems(Startup, set_volume);
+        app.configure_sets(FixedUpdate, PausableSystems.run_if(in_state(Pause(false))));
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
     }
 }
 
-/// High-level groupings of systems for the app in the `Update` schedule.
+/// High-level groupings of systems for the app in the `Update` and
+/// `FixedUpdate` schedules.
 /// When adding a new variant, make sure to order it in the `configure_sets`
 /// call above.
 #[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -117,11 +131,7 @@ struct Pause(pub bool);
 #[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 struct PausableSystems;
 
+/// [`display`] scales the camera to fit the window.
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((Name::new("Camera"), Camera2d));
 }
-
-fn set_volume(mut global_volume: ResMut<GlobalVolume>) {
-    // setting to 0.5 by default
-    global_volume.volume = Volume::Linear(0.5);
-}
@@ -5,9 +5,7 @@ use bevy::{
 };
 
 use crate::{
-    asset_tracking::LoadResource,
-    audio::music,
-    background::{BGAssets, bg_layer_1},
+    background::{Backdrop, backdrop},
     menus::Menu,
     theme::prelude::*,
 };
@@ -18,13 +16,9 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         go_back.run_if(in_state(Menu::Credits).and(input_just_pressed(KeyCode::Escape))),
     );
-
-    app.register_type::<CreditsAssets>();
-    app.load_resource::<CreditsAssets>();
-    app.add_systems(OnEnter(Menu::Credits), start_credits_music);
 }
 
-fn spawn_credits_menu(mut commands: Commands, bg_assets: Res<BGAssets>) {
+fn spawn_credits_menu(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Credits Menu"),
         GlobalZIndex(2),
@@ -37,7 +31,7 @@ fn spawn_credits_menu(mut commands: Commands, bg_assets: Res<BGAssets>) {
             widget::button("Back", go_back_on_click),
         ],
     ));
-    commands.spawn((bg_layer_1(&bg_assets), StateScoped(Menu::Credits)));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::Credits)));
 }
 
 fn created_by() -> impl Bundle {
@@ -92,27 +86,3 @@ fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<
 fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Main);
 }
-
-#[derive(Resource, Asset, Clone, Reflect)]
-#[reflect(Resource)]
-struct CreditsAssets {
-    #[dependency]
-    music: Handle<AudioSource>,
-}
-
-impl FromWorld for CreditsAssets {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
-        Self {
-            music: assets.load("audio/music/track_1.mp3"),
-        }
-    }
-}
-
-fn start_credits_music(mut commands: Commands, credits_music: Res<CreditsAssets>) {
-    commands.spawn((
-        Name::new("Credits Music"),
-        StateScoped(Menu::Credits),
-        music(credits_music.music.clone()),
-    ));
-}
@@ -0,0 +1,119 @@
+//! The display settings menu, reached from the settings menu.
+
+use bevy::{
+    input::common_conditions::input_just_pressed, prelude::*, ui::Val::*, window::PrimaryWindow,
+};
+
+use crate::{
+    background::{Backdrop, backdrop},
+    demo::field::PlayArea,
+    display::{DisplayMode, DisplaySettings, max_pixel_scale},
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::DisplaySettings), spawn_display_settings_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::DisplaySettings).and(input_just_pressed(KeyCode::Escape))),
+    );
+
+    app.add_systems(
+        Update,
+        widget::update_stepper_labels::<DisplaySettings>.run_if(in_state(Menu::DisplaySettings)),
+    );
+}
+
+fn spawn_display_settings_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Display Settings Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::DisplaySettings),
+        children![
+            widget::header("Display"),
+            display_settings_grid(),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::DisplaySettings)));
+}
+
+fn display_settings_grid() -> impl Bundle {
+    (
+        Name::new("Display Settings Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(30.0),
+            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            ..default()
+        },
+        children![
+            (
+                widget::label("Window"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::cycle(
+                "Display Mode",
+                &DisplayMode::ALL,
+                |display: &DisplaySettings| display.mode,
+                |display, mode| display.mode = mode,
+                |mode| mode.name().to_string(),
+            ),
+            (
+                widget::label("VSync"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::toggle(
+                "VSync",
+                |display: &DisplaySettings| display.vsync,
+                |display, vsync| display.vsync = vsync,
+            ),
+            (
+                widget::label("Pixel Scale"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::stepper(
+                "Pixel Scale",
+                lower_pixel_scale,
+                raise_pixel_scale,
+                |display: &DisplaySettings| match display.pixel_scale {
+                    0 => "Fit".to_string(),
+                    pixel_scale => format!("{pixel_scale}x"),
+                },
+            ),
+        ],
+    )
+}
+
+fn lower_pixel_scale(_: Trigger<Pointer<Click>>, mut display: ResMut<DisplaySettings>) {
+    display.pixel_scale = display.pixel_scale.saturating_sub(1);
+}
+
+/// Only offers scales that fit the whole play area in the window.
+fn raise_pixel_scale(
+    _: Trigger<Pointer<Click>>,
+    mut display: ResMut<DisplaySettings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    play_area: Res<PlayArea>,
+) {
+    display.pixel_scale = (display.pixel_scale + 1).min(max_pixel_scale(&window, &play_area));
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
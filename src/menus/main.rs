@@ -4,7 +4,8 @@ use bevy::{input::common_conditions::input_just_released, prelude::*};
 
 use crate::{
     asset_tracking::ResourceHandles,
-    background::{BGAssets, bg_layer_1},
+    background::{Backdrop, backdrop},
+    demo::tutorial::AfterTutorial,
     menus::Menu,
     screens::Screen,
     theme::widget,
@@ -14,52 +15,54 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
     app.add_systems(
         Update,
-        enter_loading_or_gameplay_screen_on_space
+        open_mode_select_menu_on_space
             .run_if(input_just_released(KeyCode::Space))
             .run_if(in_state(Menu::Main)),
     );
 }
 
-fn spawn_main_menu(mut commands: Commands, bg_assets: Res<BGAssets>) {
+fn spawn_main_menu(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Main Menu"),
         GlobalZIndex(2),
         StateScoped(Menu::Main),
         #[cfg(not(target_family = "wasm"))]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Play", open_mode_select_menu),
+            widget::button("Tutorial", start_tutorial),
             widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
             widget::button("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Play", open_mode_select_menu),
+            widget::button("Tutorial", start_tutorial),
             widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
         ],
     ));
-    commands.spawn((bg_layer_1(&bg_assets), StateScoped(Menu::Main)));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::Main)));
 }
 
-fn enter_loading_or_gameplay_screen(
-    _: Trigger<Pointer<Click>>,
-    resource_handles: Res<ResourceHandles>,
-    mut next_screen: ResMut<NextState<Screen>>,
-) {
-    if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
-    } else {
-        next_screen.set(Screen::Loading);
-    }
+fn open_mode_select_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ModeSelect);
 }
 
-fn enter_loading_or_gameplay_screen_on_space(
+fn open_mode_select_menu_on_space(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ModeSelect);
+}
+
+/// Replays the tutorial, then comes back here.
+fn start_tutorial(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
     resource_handles: Res<ResourceHandles>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
+    commands.insert_resource(AfterTutorial(Screen::Title));
     if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
+        next_screen.set(Screen::Tutorial);
     } else {
         next_screen.set(Screen::Loading);
     }
@@ -1,8 +1,12 @@
 //! The game's menus and transitions between them.
 
 mod credits;
+mod display_settings;
 mod main;
+pub(super) mod mode_select;
+mod online;
 mod pause;
+mod puzzle_select;
 mod settings;
 
 use bevy::prelude::*;
@@ -12,9 +16,13 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_plugins((
         credits::plugin,
+        display_settings::plugin,
         main::plugin,
+        mode_select::plugin,
         settings::plugin,
         pause::plugin,
+        puzzle_select::plugin,
+        online::plugin,
     ));
 }
 
@@ -24,7 +32,11 @@ pub enum Menu {
     #[default]
     None,
     Main,
+    ModeSelect,
+    PuzzleSelect,
+    Online,
     Credits,
     Settings,
+    DisplaySettings,
     Pause,
 }
@@ -0,0 +1,149 @@
+//! The game mode select menu, shown after pressing Play.
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    input::common_conditions::{input_just_pressed, input_just_released},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    background::{Backdrop, backdrop},
+    demo::{
+        daily::{self, DailyDay},
+        mode::GameMode,
+        rng::RunSeed,
+        tutorial::AfterTutorial,
+    },
+    menus::Menu,
+    save::SaveData,
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::ModeSelect), spawn_mode_select_menu);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+            start_selected_mode.run_if(input_just_released(KeyCode::Space)),
+        )
+            .run_if(in_state(Menu::ModeSelect)),
+    );
+}
+
+fn spawn_mode_select_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Mode Select Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::ModeSelect),
+        children![
+            widget::header("Pick a mode"),
+            mode_grid(),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::ModeSelect)));
+}
+
+/// Two columns, so every mode fits on screen.
+fn mode_grid() -> impl Bundle {
+    (
+        Name::new("Mode Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(10.0),
+            grid_template_columns: RepeatedGridTrack::auto(2),
+            ..default()
+        },
+        Children::spawn((
+            SpawnIter(GameMode::QUICK_START.into_iter().map(|game_mode| {
+                widget::button(
+                    game_mode.name(),
+                    move |_: Trigger<Pointer<Click>>,
+                          mut selected_mode: ResMut<GameMode>,
+                          run_seed: ResMut<RunSeed>,
+                          resource_handles: Res<ResourceHandles>,
+                          save_data: Res<SaveData>,
+                          commands: Commands,
+                          next_screen: ResMut<NextState<Screen>>| {
+                        *selected_mode = game_mode;
+                        start_run(
+                            game_mode,
+                            run_seed,
+                            resource_handles,
+                            save_data,
+                            commands,
+                            next_screen,
+                        );
+                    },
+                )
+            })),
+            Spawn(widget::button("Puzzle", open_puzzle_select_menu)),
+            Spawn(widget::button("Online", open_online_menu)),
+        )),
+    )
+}
+
+/// Starts the last picked mode (Endless if none was picked yet).
+fn start_selected_mode(
+    selected_mode: Res<GameMode>,
+    run_seed: ResMut<RunSeed>,
+    resource_handles: Res<ResourceHandles>,
+    save_data: Res<SaveData>,
+    commands: Commands,
+    next_screen: ResMut<NextState<Screen>>,
+) {
+    start_run(
+        *selected_mode,
+        run_seed,
+        resource_handles,
+        save_data,
+        commands,
+        next_screen,
+    );
+}
+
+/// Anyone who hasn't done the tutorial yet goes through it on the way.
+pub(super) fn start_run(
+    game_mode: GameMode,
+    mut run_seed: ResMut<RunSeed>,
+    resource_handles: Res<ResourceHandles>,
+    save_data: Res<SaveData>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let day = daily::today();
+    *run_seed = RunSeed::for_mode(game_mode, day);
+    commands.insert_resource(DailyDay(day));
+    let first_screen = if save_data.tutorial_done {
+        Screen::Gameplay
+    } else {
+        commands.insert_resource(AfterTutorial(Screen::Gameplay));
+        Screen::Tutorial
+    };
+    if resource_handles.is_all_done() {
+        next_screen.set(first_screen);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn open_puzzle_select_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::PuzzleSelect);
+}
+
+fn open_online_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Online);
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -0,0 +1,145 @@
+//! The online menu, for hosting or joining a game over the network.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+
+use crate::{
+    background::{Backdrop, backdrop},
+    demo::mode::GameMode,
+    menus::Menu,
+    net::{MAX_INPUT_DELAY, MIN_INPUT_DELAY, NetSession, NetSettings, NetStatus},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Online), spawn_online_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Online).and(input_just_pressed(KeyCode::Escape))),
+    );
+
+    app.register_type::<ConnectionStatusLabel>();
+    app.add_systems(
+        Update,
+        (
+            update_connection_status_label,
+            widget::update_stepper_labels::<NetSettings>,
+        )
+            .run_if(in_state(Menu::Online)),
+    );
+}
+
+fn spawn_online_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Online Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Online),
+        children![
+            widget::header("Online"),
+            (
+                widget::label(""),
+                TextFont::from_font_size(18.0),
+                ConnectionStatusLabel
+            ),
+            widget::button("Host Co-op", host_co_op),
+            widget::button("Host Versus", host_versus),
+            widget::button("Join", join),
+            input_delay_widget(),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::Online)));
+}
+
+fn input_delay_widget() -> impl Bundle {
+    (
+        Name::new("Input Delay Row"),
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: Px(10.0),
+            ..default()
+        },
+        children![
+            widget::label("Input Delay"),
+            widget::stepper(
+                "Input Delay",
+                lower_input_delay,
+                raise_input_delay,
+                |net_settings: &NetSettings| format!("{} frames", net_settings.input_delay),
+            ),
+        ],
+    )
+}
+
+fn host(game_mode: GameMode, commands: &mut Commands, net_status: &mut NetStatus) {
+    match NetSession::host(game_mode) {
+        Ok(session) => {
+            commands.insert_resource(session);
+            net_status.0.clear();
+        }
+        Err(err) => net_status.0 = format!("couldn't host: {err}"),
+    }
+}
+
+fn host_co_op(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut net_status: ResMut<NetStatus>,
+) {
+    host(GameMode::Endless, &mut commands, &mut net_status);
+}
+
+fn host_versus(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut net_status: ResMut<NetStatus>,
+) {
+    host(GameMode::Versus, &mut commands, &mut net_status);
+}
+
+fn join(_: Trigger<Pointer<Click>>, mut commands: Commands, mut net_status: ResMut<NetStatus>) {
+    match NetSession::join() {
+        Ok(session) => {
+            commands.insert_resource(session);
+            net_status.0.clear();
+        }
+        Err(err) => net_status.0 = format!("couldn't join: {err}"),
+    }
+}
+
+fn lower_input_delay(_: Trigger<Pointer<Click>>, mut net_settings: ResMut<NetSettings>) {
+    net_settings.input_delay = (net_settings.input_delay - 1).max(MIN_INPUT_DELAY);
+}
+
+fn raise_input_delay(_: Trigger<Pointer<Click>>, mut net_settings: ResMut<NetSettings>) {
+    net_settings.input_delay = (net_settings.input_delay + 1).min(MAX_INPUT_DELAY);
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ConnectionStatusLabel;
+
+fn update_connection_status_label(
+    session: Option<Res<NetSession>>,
+    net_status: Res<NetStatus>,
+    mut label: Single<&mut Text, With<ConnectionStatusLabel>>,
+) {
+    label.0 = match session {
+        Some(session) => session.status(),
+        None if net_status.0.is_empty() => "host a game or join one".to_string(),
+        None => net_status.0.clone(),
+    };
+}
+
+fn go_back_on_click(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    commands.remove_resource::<NetSession>();
+    next_menu.set(Menu::ModeSelect);
+}
+
+fn go_back(mut commands: Commands, mut next_menu: ResMut<NextState<Menu>>) {
+    commands.remove_resource::<NetSession>();
+    next_menu.set(Menu::ModeSelect);
+}
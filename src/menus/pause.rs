@@ -1,8 +1,20 @@
 //! The pause menu.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::{
+    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
 
-use crate::{menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    demo::{
+        enemies::EnemyController,
+        mode::{GameMode, RunClock},
+        player::PlayerCount,
+        score::ScoreController,
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::widget,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Pause), spawn_pause_menu);
@@ -12,20 +24,137 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn spawn_pause_menu(mut commands: Commands) {
+fn spawn_pause_menu(
+    mut commands: Commands,
+    score_controller_query: Query<&ScoreController>,
+    enemy_controller_query: Query<&EnemyController>,
+    run_clock: Res<RunClock>,
+    game_mode: Res<GameMode>,
+    player_count: Res<PlayerCount>,
+) {
+    let score = score_controller_query.iter().next();
+    let score_text = match (*game_mode, score) {
+        (GameMode::Versus, Some(score)) => {
+            format!(
+                "P1 {}  P2 {}",
+                score.player_scores[0], score.player_scores[1]
+            )
+        }
+        (_, Some(score)) => score.score.to_string(),
+        (_, None) => "0".to_string(),
+    };
+    let mut stats = vec![("Score", score_text)];
+    // puzzles don't level up
+    if let Some(level) = enemy_controller_query
+        .iter()
+        .map(|controller| controller.level)
+        .max()
+    {
+        stats.push(("Level", level.to_string()));
+    }
+    let seconds = run_clock.0 as u32;
+    stats.push(("Time", format!("{}:{:02}", seconds / 60, seconds % 60)));
+
     commands.spawn((
         widget::ui_root("Pause Menu"),
         GlobalZIndex(2),
         StateScoped(Menu::Pause),
         children![
             widget::header("Game paused"),
-            widget::button("Continue", close_menu),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Quit", quit_to_title),
+            (
+                Name::new("Pause Panels"),
+                Node {
+                    column_gap: Px(60.0),
+                    ..default()
+                },
+                children![
+                    info_panel("Run", stats),
+                    info_panel("Controls", controls(player_count.for_mode(*game_mode))),
+                ],
+            ),
+            (
+                Name::new("Pause Buttons"),
+                Node {
+                    display: Display::Grid,
+                    row_gap: Px(10.0),
+                    column_gap: Px(10.0),
+                    grid_template_columns: RepeatedGridTrack::auto(2),
+                    ..default()
+                },
+                children![
+                    widget::button("Continue", close_menu),
+                    widget::button("Restart", restart_run),
+                    widget::button("Settings", open_settings_menu),
+                    widget::button("Quit", quit_to_title),
+                ],
+            ),
         ],
     ));
 }
 
+/// What each control does, for however many people are playing.
+fn controls(player_count: usize) -> Vec<(&'static str, String)> {
+    let mut controls = if player_count > 1 {
+        vec![
+            ("P1", "WASD aim, Space fire".to_string()),
+            ("P2", "Arrows aim, Enter fire".to_string()),
+            ("Pad", "Stick aim, A fire (P2)".to_string()),
+        ]
+    } else {
+        vec![
+            ("Aim", "WASD / Arrows / Stick".to_string()),
+            ("Fire", "Space / Enter / A".to_string()),
+        ]
+    };
+    controls.push(("Pause", "P / Esc".to_string()));
+    controls
+}
+
+/// A titled grid of names and values.
+fn info_panel(title: &'static str, rows: Vec<(&'static str, String)>) -> impl Bundle {
+    (
+        Name::new(title),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        children![
+            widget::label(title),
+            (
+                Node {
+                    display: Display::Grid,
+                    row_gap: Px(4.0),
+                    column_gap: Px(16.0),
+                    grid_template_columns: RepeatedGridTrack::auto(2),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(rows.into_iter().flat_map(|(name, value)| {
+                    [
+                        (
+                            Text::new(name),
+                            TextFont::from_font_size(18.0),
+                            Node {
+                                justify_self: JustifySelf::End,
+                                ..default()
+                            },
+                        ),
+                        (
+                            Text::new(value),
+                            TextFont::from_font_size(18.0),
+                            Node {
+                                justify_self: JustifySelf::Start,
+                                ..default()
+                            },
+                        ),
+                    ]
+                }))),
+            ),
+        ],
+    )
+}
+
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
@@ -34,6 +163,13 @@ fn close_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>
     next_menu.set(Menu::None);
 }
 
+/// Going back through the loading screen, which moves straight on since
+/// everything's loaded, clears out the run and sets up a fresh one with the
+/// same mode, settings and seed.
+fn restart_run(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Loading);
+}
+
 fn quit_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -0,0 +1,148 @@
+//! The puzzle level select menu.
+
+use bevy::{
+    ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    background::{Backdrop, backdrop},
+    demo::{
+        mode::GameMode,
+        puzzle::{CurrentPuzzle, MAX_STARS, PuzzleAssets, PuzzlePack, is_unlocked},
+        rng::RunSeed,
+    },
+    menus::{Menu, mode_select::start_run},
+    save::SaveData,
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::PuzzleSelect), spawn_puzzle_select_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::PuzzleSelect).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// What the grid needs to know about each level.
+struct LevelCell {
+    index: usize,
+    stars: Option<u8>,
+    unlocked: bool,
+}
+
+fn spawn_puzzle_select_menu(
+    mut commands: Commands,
+    puzzle_assets: Option<Res<PuzzleAssets>>,
+    packs: Res<Assets<PuzzlePack>>,
+    save_data: Res<SaveData>,
+) {
+    let cells = puzzle_assets.map_or_else(Vec::new, |puzzle_assets| {
+        let levels = puzzle_assets.levels(&packs);
+        levels
+            .iter()
+            .enumerate()
+            .map(|(index, level)| LevelCell {
+                index,
+                stars: save_data.puzzle_stars.get(&level.name).copied(),
+                unlocked: is_unlocked(levels, index, &save_data),
+            })
+            .collect()
+    });
+    let header = if cells.is_empty() {
+        "Loading puzzles..."
+    } else {
+        "Puzzles"
+    };
+
+    commands.spawn((
+        widget::ui_root("Puzzle Select Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::PuzzleSelect),
+        children![
+            widget::header(header),
+            level_grid(cells),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::PuzzleSelect)));
+}
+
+fn level_grid(cells: Vec<LevelCell>) -> impl Bundle {
+    (
+        Name::new("Level Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(20.0),
+            grid_template_columns: RepeatedGridTrack::px(5, 100.0),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            for cell in cells {
+                let stars_text = match cell.stars {
+                    Some(stars) => format!(
+                        "{}{}",
+                        "*".repeat(stars.into()),
+                        "-".repeat((MAX_STARS - stars).into())
+                    ),
+                    None if cell.unlocked => "new".to_string(),
+                    None => "locked".to_string(),
+                };
+                let mut cell_entity = parent.spawn((
+                    Name::new("Level Cell"),
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ));
+                let index = cell.index;
+                if cell.unlocked {
+                    cell_entity.with_child(widget::button_medium(
+                        format!("{}", index + 1),
+                        move |_: Trigger<Pointer<Click>>,
+                              mut current_puzzle: ResMut<CurrentPuzzle>,
+                              mut game_mode: ResMut<GameMode>,
+                              run_seed: ResMut<RunSeed>,
+                              resource_handles: Res<ResourceHandles>,
+                              save_data: Res<SaveData>,
+                              commands: Commands,
+                              next_screen: ResMut<NextState<Screen>>| {
+                            current_puzzle.0 = index;
+                            *game_mode = GameMode::Puzzle;
+                            start_run(
+                                GameMode::Puzzle,
+                                run_seed,
+                                resource_handles,
+                                save_data,
+                                commands,
+                                next_screen,
+                            );
+                        },
+                    ));
+                } else {
+                    cell_entity.with_child((
+                        widget::label(format!("{}", index + 1)),
+                        Node {
+                            height: Px(80.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                    ));
+                }
+                cell_entity.with_child((widget::label(stars_text), TextFont::from_font_size(16.0)));
+            }
+        })),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ModeSelect);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ModeSelect);
+}
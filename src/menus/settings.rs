@@ -5,9 +5,18 @@
 use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
 
 use crate::{
-    background::{BGAssets, bg_layer_1},
+    audio::{MuteWhenUnfocused, PositionalAudio},
+    background::{Backdrop, backdrop},
+    demo::{
+        camera_effects::ShakeLevel,
+        difficulty::AdaptiveDifficulty,
+        particles::ParticleQuality,
+        player::{MAX_PLAYERS, PlayerCount},
+        simulation::{TICK_RATES, TickRate},
+    },
     menus::Menu,
-    screens::Screen,
+    save::{Preferences, SaveData},
+    screens::{Screen, gameplay::AutoPause},
     theme::prelude::*,
 };
 
@@ -17,15 +26,27 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
     );
+    app.add_systems(Startup, load_preferences);
+    app.add_systems(Update, save_preferences);
 
-    app.register_type::<GlobalVolumeLabel>();
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        (
+            widget::update_stepper_labels::<GlobalVolume>,
+            widget::update_stepper_labels::<PositionalAudio>,
+            widget::update_stepper_labels::<MuteWhenUnfocused>,
+            widget::update_stepper_labels::<AutoPause>,
+            widget::update_stepper_labels::<AdaptiveDifficulty>,
+            widget::update_stepper_labels::<PlayerCount>,
+            widget::update_stepper_labels::<TickRate>,
+            widget::update_stepper_labels::<ParticleQuality>,
+            widget::update_stepper_labels::<ShakeLevel>,
+        )
+            .run_if(in_state(Menu::Settings)),
     );
 }
 
-fn spawn_settings_menu(mut commands: Commands, bg_assets: Res<BGAssets>) {
+fn spawn_settings_menu(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Settings Menu"),
         GlobalZIndex(2),
@@ -33,10 +54,11 @@ fn spawn_settings_menu(mut commands: Commands, bg_assets: Res<BGAssets>) {
         children![
             widget::header("Settings"),
             settings_grid(),
+            widget::button("Display", open_display_settings_menu),
             widget::button("Back", go_back_on_click),
         ],
     ));
-    commands.spawn((bg_layer_1(&bg_assets), StateScoped(Menu::Credits)));
+    commands.spawn((backdrop(Backdrop::Menu), StateScoped(Menu::Settings)));
 }
 
 fn settings_grid() -> impl Bundle {
@@ -57,30 +79,124 @@ fn settings_grid() -> impl Bundle {
                     ..default()
                 }
             ),
-            global_volume_widget(),
-        ],
-    )
-}
-
-fn global_volume_widget() -> impl Bundle {
-    (
-        Name::new("Global Volume Widget"),
-        Node {
-            justify_self: JustifySelf::Start,
-            ..default()
-        },
-        children![
-            widget::button_small("-", lower_global_volume),
+            widget::stepper(
+                "Volume",
+                lower_global_volume,
+                raise_global_volume,
+                |global_volume: &GlobalVolume| {
+                    let percent = 100.0 * global_volume.volume.to_linear();
+                    format!("{percent:3.0}%")
+                },
+            ),
+            (
+                widget::label("Positional Audio"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::toggle(
+                "Positional Audio",
+                |positional_audio: &PositionalAudio| positional_audio.enabled,
+                |positional_audio, enabled| positional_audio.enabled = enabled,
+            ),
             (
-                Name::new("Current Volume"),
+                widget::label("Mute When Unfocused"),
                 Node {
-                    padding: UiRect::horizontal(Px(10.0)),
-                    justify_content: JustifyContent::Center,
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::toggle(
+                "Mute When Unfocused",
+                |mute_when_unfocused: &MuteWhenUnfocused| mute_when_unfocused.enabled,
+                |mute_when_unfocused, enabled| mute_when_unfocused.enabled = enabled,
+            ),
+            (
+                widget::label("Pause When Unfocused"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::toggle(
+                "Auto Pause",
+                |auto_pause: &AutoPause| auto_pause.enabled,
+                |auto_pause, enabled| auto_pause.enabled = enabled,
+            ),
+            (
+                widget::label("Adaptive Difficulty"),
+                Node {
+                    justify_self: JustifySelf::End,
                     ..default()
+                }
+            ),
+            widget::toggle(
+                "Adaptive Difficulty",
+                |difficulty: &AdaptiveDifficulty| difficulty.enabled,
+                |difficulty, enabled| difficulty.enabled = enabled,
+            ),
+            (
+                widget::label("Players"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::stepper(
+                "Player Count",
+                remove_player,
+                add_player,
+                |player_count: &PlayerCount| {
+                    if player_count.0 > 1 {
+                        format!("{} (co-op)", player_count.0)
+                    } else {
+                        "1".to_string()
+                    }
                 },
-                children![(widget::label(""), GlobalVolumeLabel)],
             ),
-            widget::button_small("+", raise_global_volume),
+            (
+                widget::label("Tick Rate"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::cycle(
+                "Tick Rate",
+                &TICK_RATES,
+                |tick_rate: &TickRate| tick_rate.0,
+                |tick_rate, rate| tick_rate.0 = rate,
+                |rate| format!("{rate} Hz"),
+            ),
+            (
+                widget::label("Particles"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::cycle(
+                "Particle Quality",
+                &ParticleQuality::ALL,
+                |quality: &ParticleQuality| *quality,
+                |quality, option| *quality = option,
+                |quality| quality.name().to_string(),
+            ),
+            (
+                widget::label("Screen Shake"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::cycle(
+                "Screen Shake",
+                &ShakeLevel::ALL,
+                |shake_level: &ShakeLevel| *shake_level,
+                |shake_level, option| *shake_level = option,
+                |shake_level| shake_level.name().to_string(),
+            ),
         ],
     )
 }
@@ -98,16 +214,58 @@ fn raise_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<Glo
     global_volume.volume = Volume::Linear(linear);
 }
 
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-struct GlobalVolumeLabel;
+fn remove_player(_: Trigger<Pointer<Click>>, mut player_count: ResMut<PlayerCount>) {
+    player_count.0 = (player_count.0 - 1).max(1);
+}
+
+fn add_player(_: Trigger<Pointer<Click>>, mut player_count: ResMut<PlayerCount>) {
+    player_count.0 = (player_count.0 + 1).min(MAX_PLAYERS);
+}
+
+fn load_preferences(
+    save_data: Res<SaveData>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut positional_audio: ResMut<PositionalAudio>,
+    mut mute_when_unfocused: ResMut<MuteWhenUnfocused>,
+    mut auto_pause: ResMut<AutoPause>,
+    mut particle_quality: ResMut<ParticleQuality>,
+    mut shake_level: ResMut<ShakeLevel>,
+) {
+    let preferences = save_data.preferences;
+    global_volume.volume = Volume::Linear(preferences.volume.clamp(MIN_VOLUME, MAX_VOLUME));
+    positional_audio.enabled = preferences.positional_audio;
+    mute_when_unfocused.enabled = preferences.mute_when_unfocused;
+    auto_pause.enabled = preferences.auto_pause;
+    *particle_quality = preferences.particles;
+    *shake_level = preferences.shake;
+}
 
-fn update_global_volume_label(
+fn save_preferences(
     global_volume: Res<GlobalVolume>,
-    mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
+    positional_audio: Res<PositionalAudio>,
+    mute_when_unfocused: Res<MuteWhenUnfocused>,
+    auto_pause: Res<AutoPause>,
+    particle_quality: Res<ParticleQuality>,
+    shake_level: Res<ShakeLevel>,
+    mut save_data: ResMut<SaveData>,
 ) {
-    let percent = 100.0 * global_volume.volume.to_linear();
-    label.0 = format!("{percent:3.0}%");
+    let preferences = Preferences {
+        volume: global_volume.volume.to_linear(),
+        positional_audio: positional_audio.enabled,
+        mute_when_unfocused: mute_when_unfocused.enabled,
+        auto_pause: auto_pause.enabled,
+        particles: *particle_quality,
+        shake: *shake_level,
+    };
+    // only touch the save data when something changed, so it isn't
+    // rewritten every frame
+    if save_data.preferences != preferences {
+        save_data.preferences = preferences;
+    }
+}
+
+fn open_display_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::DisplaySettings);
 }
 
 fn go_back_on_click(
@@ -0,0 +1,804 @@
+//! Online co-op and versus over UDP, kept in sync with lockstep.
+//!
+//! Both games run the same simulation and only swap each frame's inputs. A
+//! frame only runs once both players' inputs for it have arrived, so inputs
+//! are sent a few frames ahead of when they're used (the input delay) to
+//! hide the round trip. Each lockstep frame is one gameplay tick at the
+//! host's tick rate. Real time is banked up between rendered frames, and a
+//! rendered frame only runs a tick once a whole timestep has built up and
+//! the inputs are in, so both sides see the same `Time` and play at real
+//! speed whatever their frame rate. A game that renders slower than the tick
+//! rate holds the other one back.
+//!
+//! Every so often both sides hash the state of enemies, explosions and
+//! buildings and swap the hashes, so a desync gets noticed instead of the
+//! two games quietly drifting apart.
+//!
+//! To try it on one machine, start two copies of the game, host from one and
+//! join from the other. Joining connects to `127.0.0.1:7777` unless
+//! `CHAINVASION_HOST` says otherwise.
+
+use std::{
+    collections::{BTreeMap, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::ResourceHandles,
+    demo::{
+        buildings::{Building, Destroyed},
+        daily,
+        difficulty::AdaptiveDifficulty,
+        explosions::{Explosion, ExplosionController},
+        floating_text::NewText,
+        mode::GameMode,
+        player::{MAX_PLAYERS, Player, PlayerCount, PlayerInput, apply_player_input, local_input},
+        rng::RunSeed,
+        simulation::{TICK_RATES, TickRate},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<NetSettings>();
+    app.init_resource::<NetStatus>();
+
+    // Read the socket and decide whether this frame can run before time
+    // advances, so a stalled frame doesn't move the clock.
+    app.add_systems(
+        First,
+        (receive_messages, update_lockstep_clock)
+            .chain()
+            .before(TimeSystem)
+            .run_if(resource_exists::<NetSession>),
+    );
+    app.configure_sets(Update, PausableSystems.run_if(lockstep_frame_ready));
+    app.configure_sets(FixedUpdate, PausableSystems.run_if(lockstep_frame_ready));
+
+    app.add_systems(
+        Update,
+        (
+            keep_handshaking.run_if(not(in_state(Screen::Gameplay))),
+            start_online_run,
+            send_inputs,
+            drop_lost_connection.run_if(in_state(Screen::Gameplay)),
+        )
+            .chain()
+            .run_if(resource_exists::<NetSession>),
+    );
+    app.add_systems(
+        FixedUpdate,
+        use_lockstep_inputs
+            .before(apply_player_input)
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems)
+            .run_if(resource_exists::<NetSession>),
+    );
+    app.add_systems(
+        FixedPostUpdate,
+        compare_checksums.run_if(in_state(Screen::Gameplay).and(resource_exists::<NetSession>)),
+    );
+    app.add_systems(
+        Last,
+        finish_lockstep_frame.run_if(in_state(Screen::Gameplay).and(resource_exists::<NetSession>)),
+    );
+    app.add_systems(OnEnter(Screen::Title), end_session);
+}
+
+/// The port hosts listen on.
+const DEFAULT_PORT: u16 = 7777;
+/// Each input packet repeats this many of the latest frames, so one lost
+/// packet doesn't stall the other side.
+const REDUNDANT_FRAMES: u32 = 8;
+/// Frames between state checksums.
+const CHECKSUM_INTERVAL: u32 = 60;
+/// How often a joining game asks again if the host hasn't answered.
+const HELLO_INTERVAL: Duration = Duration::from_millis(500);
+/// How long to wait on a silent peer before giving up on the match.
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Real time banked beyond this many ticks is dropped, so a stall doesn't
+/// turn into a burst of catching up.
+const MAX_BANKED_TICKS: u32 = 2;
+
+pub const MIN_INPUT_DELAY: u32 = 1;
+pub const MAX_INPUT_DELAY: u32 = 10;
+
+/// Online options picked in the online menu.
+#[derive(Resource, Debug)]
+pub struct NetSettings {
+    /// Frames between reading an input and using it. Higher hides more
+    /// latency but makes the controls feel heavier.
+    pub input_delay: u32,
+}
+
+impl Default for NetSettings {
+    fn default() -> Self {
+        Self { input_delay: 3 }
+    }
+}
+
+/// What the online menu should tell the player about the connection.
+#[derive(Resource, Debug, Default)]
+pub struct NetStatus(pub String);
+
+/// One frame of a player's input, squashed down to go over the wire. Both
+/// sides play from these rather than the raw input, so they agree exactly.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct NetInput {
+    x: i8,
+    y: i8,
+    fire: bool,
+}
+
+impl From<PlayerInput> for NetInput {
+    fn from(input: PlayerInput) -> Self {
+        let quantize = |axis: f32| (axis.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8;
+        Self {
+            x: quantize(input.movement.x),
+            y: quantize(input.movement.y),
+            fire: input.fire,
+        }
+    }
+}
+
+impl From<NetInput> for PlayerInput {
+    fn from(input: NetInput) -> Self {
+        Self {
+            movement: Vec2::new(input.x.into(), input.y.into()) / i8::MAX as f32,
+            fire: input.fire,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum Message {
+    /// A joining game asking to play.
+    Hello,
+    /// The host's answer, with everything needed to start the same run.
+    Welcome {
+        game_mode: GameMode,
+        seed: u64,
+        input_delay: u32,
+        adaptive_difficulty: bool,
+        tick_rate: u32,
+    },
+    /// The sender's inputs from `first_frame` on.
+    Inputs {
+        first_frame: u32,
+        inputs: Vec<NetInput>,
+    },
+    /// A hash of the sender's game state at the end of `frame`.
+    Checksum { frame: u32, hash: u64 },
+}
+
+impl Message {
+    /// Messages go over the wire as RON text, one per packet.
+    fn encode(&self) -> Result<String, ron::Error> {
+        ron::to_string(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        std::str::from_utf8(bytes)
+            .map_err(|err| err.to_string())
+            .and_then(|text| ron::from_str(text).map_err(|err| err.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Host,
+    Join,
+}
+
+/// Settings an online run overrides, put back when the session ends.
+#[derive(Debug, Clone, Copy)]
+struct SavedSettings {
+    player_count: PlayerCount,
+    adaptive_difficulty: bool,
+    tick_rate: TickRate,
+}
+
+/// An online match, from hosting or joining until it's back to the title.
+#[derive(Resource)]
+pub struct NetSession {
+    socket: UdpSocket,
+    role: Role,
+    peer: Option<SocketAddr>,
+    game_mode: GameMode,
+    /// Set once both sides know the run, until the run starts. For the host
+    /// that's the joining game's hello, for the joining game the host's welcome.
+    pending_start: Option<Message>,
+    /// What the host told the joining game, in case it needs telling again.
+    welcome: Option<Message>,
+    started: bool,
+    input_delay: u32,
+    /// The frame being simulated.
+    frame: u32,
+    /// Whether both inputs for `frame` are in and enough time has passed to
+    /// run it.
+    frame_ready: bool,
+    /// Real time that hasn't been spent on ticks yet.
+    banked_time: Duration,
+    /// When the lockstep clock last checked the time.
+    last_clock: Option<Instant>,
+    local_inputs: BTreeMap<u32, NetInput>,
+    remote_inputs: BTreeMap<u32, NetInput>,
+    local_checksums: BTreeMap<u32, u64>,
+    remote_checksums: BTreeMap<u32, u64>,
+    desynced: bool,
+    last_heard: Instant,
+    last_hello: Option<Instant>,
+    saved_settings: Option<SavedSettings>,
+}
+
+impl NetSession {
+    /// Starts listening for someone to join a run of `game_mode`.
+    pub fn host(game_mode: GameMode) -> std::io::Result<Self> {
+        Self::bind(
+            Role::Host,
+            game_mode,
+            SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT)),
+            None,
+        )
+    }
+
+    /// Starts asking the host (see the module docs) to let us in.
+    pub fn join() -> std::io::Result<Self> {
+        let host = std::env::var("CHAINVASION_HOST")
+            .ok()
+            .and_then(|host| host.parse().ok())
+            .unwrap_or(SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT)));
+        Self::bind(
+            Role::Join,
+            GameMode::default(),
+            SocketAddr::from(([0, 0, 0, 0], 0)),
+            Some(host),
+        )
+    }
+
+    fn bind(
+        role: Role,
+        game_mode: GameMode,
+        addr: SocketAddr,
+        peer: Option<SocketAddr>,
+    ) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            role,
+            peer,
+            game_mode,
+            pending_start: None,
+            welcome: None,
+            started: false,
+            input_delay: MIN_INPUT_DELAY,
+            frame: 0,
+            frame_ready: false,
+            banked_time: Duration::ZERO,
+            last_clock: None,
+            local_inputs: BTreeMap::new(),
+            remote_inputs: BTreeMap::new(),
+            local_checksums: BTreeMap::new(),
+            remote_checksums: BTreeMap::new(),
+            desynced: false,
+            last_heard: Instant::now(),
+            last_hello: None,
+            saved_settings: None,
+        })
+    }
+
+    /// Which player this game controls. The host is always player one.
+    fn local_player(&self) -> usize {
+        match self.role {
+            Role::Host => 0,
+            Role::Join => 1,
+        }
+    }
+
+    pub fn status(&self) -> String {
+        match (self.role, self.peer) {
+            (Role::Host, None) => format!("waiting for a player on port {DEFAULT_PORT}"),
+            (Role::Host, Some(peer)) => format!("{peer} joined"),
+            (Role::Join, Some(peer)) => format!("joining {peer}"),
+            (Role::Join, None) => "no host to join".to_string(),
+        }
+    }
+
+    fn send(&self, message: &Message) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        match message.encode() {
+            Ok(text) => {
+                if let Err(err) = self.socket.send_to(text.as_bytes(), peer) {
+                    warn!("Couldn't send to {peer}: {err}");
+                }
+            }
+            Err(err) => warn!("Couldn't serialize {message:?}: {err}"),
+        }
+    }
+
+    /// The first `input_delay` frames run before anyone's input could have
+    /// arrived, so both sides fill them in with no input.
+    fn fill_delay_frames(&mut self) {
+        for frame in 0..self.input_delay {
+            self.local_inputs.insert(frame, NetInput::default());
+            self.remote_inputs.insert(frame, NetInput::default());
+        }
+    }
+}
+
+fn receive_messages(mut session: ResMut<NetSession>) {
+    let mut buffer = [0; 2048];
+    loop {
+        let (len, from) = match session.socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("Couldn't receive: {err}");
+                break;
+            }
+        };
+        let message = match Message::decode(&buffer[..len]) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Ignoring a bad packet from {from}: {err}");
+                continue;
+            }
+        };
+        // only the first game to say hello gets to play
+        if session.peer.is_some_and(|peer| peer != from) {
+            continue;
+        }
+        session.last_heard = Instant::now();
+
+        match message {
+            Message::Hello if session.role == Role::Host => {
+                if session.peer.is_none() {
+                    session.peer = Some(from);
+                    session.pending_start = Some(Message::Hello);
+                } else if let Some(welcome) = session.welcome.clone() {
+                    // they're still asking, so the welcome must have got lost
+                    session.send(&welcome);
+                }
+            }
+            welcome @ Message::Welcome { .. }
+                if session.role == Role::Join
+                    && !session.started
+                    && session.pending_start.is_none() =>
+            {
+                session.pending_start = Some(welcome);
+            }
+            Message::Inputs {
+                first_frame,
+                inputs,
+            } => {
+                for (frame, input) in (first_frame..).zip(inputs) {
+                    if frame >= session.frame {
+                        session.remote_inputs.insert(frame, input);
+                    }
+                }
+            }
+            Message::Checksum { frame, hash } => {
+                session.remote_checksums.insert(frame, hash);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_lockstep_clock(
+    mut session: ResMut<NetSession>,
+    screen: Res<State<Screen>>,
+    fixed_time: Res<Time<Fixed>>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let in_gameplay = *screen.get() == Screen::Gameplay;
+    let now = Instant::now();
+    let elapsed = session
+        .last_clock
+        .map_or(Duration::ZERO, |last_clock| now - last_clock);
+    session.last_clock = Some(now);
+
+    let timestep = fixed_time.timestep();
+    session.banked_time = if in_gameplay {
+        (session.banked_time + elapsed).min(timestep * MAX_BANKED_TICKS)
+    } else {
+        Duration::ZERO
+    };
+    session.frame_ready = in_gameplay
+        && session.banked_time >= timestep
+        && session.remote_inputs.contains_key(&session.frame);
+    if session.frame_ready {
+        session.banked_time -= timestep;
+    }
+    *time_update_strategy = match (in_gameplay, session.frame_ready) {
+        // exactly enough time for one gameplay tick
+        (true, true) => TimeUpdateStrategy::ManualDuration(timestep),
+        (true, false) => TimeUpdateStrategy::ManualDuration(Duration::ZERO),
+        (false, _) => TimeUpdateStrategy::Automatic,
+    };
+}
+
+/// Gameplay only moves on when both players' inputs for the frame are in.
+fn lockstep_frame_ready(session: Option<Res<NetSession>>) -> bool {
+    session.is_none_or(|session| session.frame_ready)
+}
+
+/// Keeps asking until the host answers.
+fn keep_handshaking(mut session: ResMut<NetSession>) {
+    if session.role != Role::Join || session.started {
+        return;
+    }
+    let due = session
+        .last_hello
+        .is_none_or(|last_hello| last_hello.elapsed() >= HELLO_INTERVAL);
+    if due {
+        session.send(&Message::Hello);
+        session.last_hello = Some(Instant::now());
+    }
+}
+
+fn start_online_run(
+    mut session: ResMut<NetSession>,
+    mut game_mode: ResMut<GameMode>,
+    mut run_seed: ResMut<RunSeed>,
+    mut player_count: ResMut<PlayerCount>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut tick_rate: ResMut<TickRate>,
+    net_settings: Res<NetSettings>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(start) = session.pending_start.take() else {
+        return;
+    };
+    let (mode, seed, input_delay, adaptive_difficulty, rate) = match start {
+        // the host picks the run and tells the joining game about it
+        Message::Hello => {
+            let seed = RunSeed::for_mode(session.game_mode, daily::today()).0;
+            (
+                session.game_mode,
+                seed,
+                net_settings.input_delay,
+                adaptive.enabled,
+                tick_rate.0,
+            )
+        }
+        Message::Welcome {
+            game_mode,
+            seed,
+            input_delay,
+            adaptive_difficulty,
+            tick_rate,
+        } => (game_mode, seed, input_delay, adaptive_difficulty, tick_rate),
+        _ => return,
+    };
+    session.game_mode = mode;
+    session.input_delay = input_delay.clamp(MIN_INPUT_DELAY, MAX_INPUT_DELAY);
+    session.fill_delay_frames();
+    session.saved_settings = Some(SavedSettings {
+        player_count: *player_count,
+        adaptive_difficulty: adaptive.enabled,
+        tick_rate: *tick_rate,
+    });
+    session.started = true;
+    if session.role == Role::Host {
+        let welcome = Message::Welcome {
+            game_mode: mode,
+            seed,
+            input_delay: session.input_delay,
+            adaptive_difficulty,
+            tick_rate: rate,
+        };
+        session.send(&welcome);
+        session.welcome = Some(welcome);
+    }
+
+    *game_mode = mode;
+    *run_seed = RunSeed(seed);
+    player_count.0 = MAX_PLAYERS;
+    adaptive.enabled = adaptive_difficulty;
+    if TICK_RATES.contains(&rate) {
+        tick_rate.0 = rate;
+    }
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn send_inputs(session: Res<NetSession>) {
+    if !session.started {
+        return;
+    }
+    let Some(&first_frame) = session.local_inputs.keys().next() else {
+        return;
+    };
+    session.send(&Message::Inputs {
+        first_frame,
+        inputs: session.local_inputs.values().copied().collect(),
+    });
+}
+
+/// Swaps in the inputs both sides agreed on for this frame, and queues up
+/// what's pressed now to be played `input_delay` frames from now.
+fn use_lockstep_inputs(
+    mut session: ResMut<NetSession>,
+    input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut player_query: Query<(&Player, &mut PlayerInput)>,
+) {
+    let ahead = session.frame + session.input_delay;
+    let local = local_input(&input, gamepads.iter().next());
+    session.local_inputs.insert(ahead, local.into());
+
+    let local_player = session.local_player();
+    for (player, mut player_input) in &mut player_query {
+        let inputs = if player.index == local_player {
+            &session.local_inputs
+        } else {
+            &session.remote_inputs
+        };
+        *player_input = inputs
+            .get(&session.frame)
+            .copied()
+            .unwrap_or_default()
+            .into();
+    }
+}
+
+fn drop_lost_connection(
+    session: Res<NetSession>,
+    mut net_status: ResMut<NetStatus>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if session.last_heard.elapsed() > DISCONNECT_TIMEOUT {
+        warn!("Lost connection to {:?}", session.peer);
+        net_status.0 = "lost connection".to_string();
+        next_screen.set(Screen::Title);
+    }
+}
+
+/// Hashes everything that has to match between the two games. Summing the
+/// per-entity hashes keeps the result the same whatever order they're in.
+fn state_checksum(
+    enemies: &Query<&Transform, With<ExplosionController>>,
+    explosions: &Query<(&Transform, &Explosion)>,
+    buildings: &Query<(&Transform, &Building), Without<Destroyed>>,
+) -> u64 {
+    fn hash_of(value: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+    let position = |transform: &Transform| {
+        (
+            transform.translation.x.to_bits(),
+            transform.translation.y.to_bits(),
+        )
+    };
+
+    let enemies = enemies
+        .iter()
+        .map(|transform| hash_of(("enemy", position(transform))));
+    let explosions = explosions.iter().map(|(transform, explosion)| {
+        hash_of(("explosion", position(transform), explosion.radius.to_bits()))
+    });
+    let buildings = buildings
+        .iter()
+        .map(|(transform, building)| hash_of(("building", position(transform), building.health)));
+    enemies
+        .chain(explosions)
+        .chain(buildings)
+        .fold(0, u64::wrapping_add)
+}
+
+fn compare_checksums(
+    mut session: ResMut<NetSession>,
+    enemies: Query<&Transform, With<ExplosionController>>,
+    explosions: Query<(&Transform, &Explosion)>,
+    // rubble settles at whatever frame rate each game runs at
+    buildings: Query<(&Transform, &Building), Without<Destroyed>>,
+    mut net_status: ResMut<NetStatus>,
+    mut ev_new_text: EventWriter<NewText>,
+) {
+    if session.frame_ready && (session.frame + 1).is_multiple_of(CHECKSUM_INTERVAL) {
+        let frame = session.frame;
+        let hash = state_checksum(&enemies, &explosions, &buildings);
+        session.local_checksums.insert(frame, hash);
+        session.send(&Message::Checksum { frame, hash });
+    }
+
+    let checked = session
+        .local_checksums
+        .iter()
+        .filter_map(|(&frame, &hash)| {
+            session
+                .remote_checksums
+                .get(&frame)
+                .map(|&remote_hash| (frame, hash == remote_hash))
+        })
+        .collect::<Vec<_>>();
+    for (frame, matches) in checked {
+        session.local_checksums.remove(&frame);
+        session.remote_checksums.remove(&frame);
+        if !matches && !session.desynced {
+            error!("Desync detected at frame {frame}");
+            session.desynced = true;
+            net_status.0 = format!("desynced at frame {frame}");
+            ev_new_text.write(NewText("desync!".to_string(), 0.0, 150.0));
+        }
+    }
+}
+
+fn finish_lockstep_frame(mut session: ResMut<NetSession>) {
+    if !session.frame_ready {
+        return;
+    }
+    session.frame += 1;
+    session.frame_ready = false;
+    let frame = session.frame;
+    let newest = frame + session.input_delay;
+    session
+        .remote_inputs
+        .retain(|&input_frame, _| input_frame >= frame);
+    session
+        .local_inputs
+        .retain(|&input_frame, _| input_frame + REDUNDANT_FRAMES > newest);
+}
+
+fn end_session(
+    mut commands: Commands,
+    session: Option<Res<NetSession>>,
+    mut player_count: ResMut<PlayerCount>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut tick_rate: ResMut<TickRate>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let Some(session) = session else {
+        return;
+    };
+    if let Some(saved) = session.saved_settings {
+        *player_count = saved.player_count;
+        adaptive.enabled = saved.adaptive_difficulty;
+        *tick_rate = saved.tick_rate;
+    }
+    *time_update_strategy = TimeUpdateStrategy::Automatic;
+    commands.remove_resource::<NetSession>();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn round_trip(message: Message) {
+        let text = message.encode().unwrap();
+        assert_eq!(Message::decode(text.as_bytes()).unwrap(), message);
+    }
+
+    #[test]
+    fn messages_survive_the_wire() {
+        round_trip(Message::Hello);
+        round_trip(Message::Welcome {
+            game_mode: GameMode::Versus,
+            seed: u64::MAX,
+            input_delay: MAX_INPUT_DELAY,
+            adaptive_difficulty: true,
+            tick_rate: TICK_RATES[0],
+        });
+        round_trip(Message::Inputs {
+            first_frame: 42,
+            inputs: vec![
+                NetInput::default(),
+                NetInput {
+                    x: i8::MIN,
+                    y: i8::MAX,
+                    fire: true,
+                },
+            ],
+        });
+        round_trip(Message::Checksum {
+            frame: u32::MAX,
+            hash: 0xdead_beef_cafe_f00d,
+        });
+    }
+
+    #[test]
+    fn bad_packets_are_rejected() {
+        assert!(Message::decode(b"Welcome(").is_err());
+        assert!(Message::decode(&[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn inputs_keep_their_extremes() {
+        let input = PlayerInput {
+            movement: Vec2::new(-1.0, 1.0),
+            fire: true,
+        };
+        let decoded = PlayerInput::from(NetInput::from(input));
+        assert_eq!(decoded.movement, input.movement);
+        assert!(decoded.fire);
+    }
+
+    fn checksum(world: &mut World) -> u64 {
+        world
+            .run_system_once(
+                |enemies: Query<&Transform, With<ExplosionController>>,
+                 explosions: Query<(&Transform, &Explosion)>,
+                 buildings: Query<(&Transform, &Building), Without<Destroyed>>| {
+                    state_checksum(&enemies, &explosions, &buildings)
+                },
+            )
+            .unwrap()
+    }
+
+    fn enemy(x: f32) -> impl Bundle {
+        (
+            Transform::from_xyz(x, 100.0, 0.0),
+            ExplosionController::default(),
+        )
+    }
+
+    fn building(x: f32, health: i32) -> impl Bundle {
+        (
+            Transform::from_xyz(x, -200.0, 0.0),
+            Building {
+                health,
+                last_damage_time: 0.0,
+            },
+        )
+    }
+
+    #[test]
+    fn checksum_ignores_spawn_order() {
+        let mut world = World::new();
+        world.spawn(enemy(-50.0));
+        world.spawn(enemy(50.0));
+        world.spawn(building(0.0, 3));
+
+        let mut reversed = World::new();
+        reversed.spawn(building(0.0, 3));
+        reversed.spawn(enemy(50.0));
+        reversed.spawn(enemy(-50.0));
+
+        assert_eq!(checksum(&mut world), checksum(&mut reversed));
+    }
+
+    #[test]
+    fn checksum_notices_differences() {
+        let mut world = World::new();
+        world.spawn(enemy(0.0));
+        world.spawn(building(0.0, 3));
+        let before = checksum(&mut world);
+
+        let mut damaged = World::new();
+        damaged.spawn(enemy(0.0));
+        damaged.spawn(building(0.0, 2));
+        assert_ne!(before, checksum(&mut damaged));
+
+        let mut exploding = World::new();
+        exploding.spawn(enemy(0.0));
+        exploding.spawn(building(0.0, 3));
+        exploding.spawn((
+            Transform::from_xyz(0.0, 100.0, 0.0),
+            Explosion::new(100.0, Vec2::new(0.0, 100.0), 1.0),
+        ));
+        assert_ne!(before, checksum(&mut exploding));
+    }
+}
@@ -0,0 +1,151 @@
+//! Player data that should survive between sessions, like best scores.
+//!
+//! Native builds keep this in a RON file in the user's data directory, and web
+//! builds keep it in the browser's local storage.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    demo::{camera_effects::ShakeLevel, mode::HighScores, particles::ParticleQuality},
+    display::DisplaySettings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(SaveData::load());
+    app.add_systems(
+        Update,
+        write_save_data.run_if(resource_changed::<SaveData>.and(not(resource_added::<SaveData>))),
+    );
+}
+
+/// Everything we persist. New fields should have a sensible [`Default`] so
+/// older saves keep loading.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SaveData {
+    /// The best few scores for each mode.
+    pub high_scores: HighScores,
+    /// Best daily challenge score, along with the day it was set on.
+    pub daily_best: Option<DailyBest>,
+    /// Best star rating for each cleared puzzle level, keyed by level name.
+    pub puzzle_stars: BTreeMap<String, u8>,
+    /// Window mode, vsync and pixel scale.
+    pub display: DisplaySettings,
+    /// Everything else picked in the settings menu that's a matter of taste
+    /// rather than how a run plays.
+    pub preferences: Preferences,
+    /// Whether the tutorial has been finished or skipped, so it only starts
+    /// on its own the first time someone plays.
+    pub tutorial_done: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Preferences {
+    /// Master volume, as a linear multiplier.
+    pub volume: f32,
+    pub positional_audio: bool,
+    pub mute_when_unfocused: bool,
+    pub auto_pause: bool,
+    pub particles: ParticleQuality,
+    pub shake: ShakeLevel,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            positional_audio: true,
+            mute_when_unfocused: true,
+            auto_pause: true,
+            particles: ParticleQuality::default(),
+            shake: ShakeLevel::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyBest {
+    /// Days since the Unix epoch (UTC).
+    pub day: u64,
+    pub score: u32,
+}
+
+impl SaveData {
+    fn load() -> Self {
+        let Some(contents) = read_save() else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable save data: {err}");
+            Self::default()
+        })
+    }
+}
+
+fn write_save_data(save_data: Res<SaveData>) {
+    match ron::to_string(&*save_data) {
+        Ok(contents) => write_save(&contents),
+        Err(err) => warn!("Couldn't serialize save data: {err}"),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_path() -> Option<std::path::PathBuf> {
+    use std::{env, path::PathBuf};
+
+    let data_dir = env::var_os("APPDATA")
+        .or_else(|| env::var_os("XDG_DATA_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+    Some(data_dir.join("chainvasion").join("save.ron"))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_save() -> Option<String> {
+    std::fs::read_to_string(save_path()?).ok()
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_save(contents: &str) {
+    let Some(path) = save_path() else {
+        warn!("No data directory to save to");
+        return;
+    };
+    if let Some(parent) = path.parent()
+        && let Err(err) = std::fs::create_dir_all(parent)
+    {
+        warn!("Couldn't create save directory {}: {err}", parent.display());
+        return;
+    }
+    if let Err(err) = std::fs::write(&path, contents) {
+        warn!("Couldn't write save data to {}: {err}", path.display());
+    }
+}
+
+#[cfg(target_family = "wasm")]
+const STORAGE_KEY: &str = "chainvasion_save";
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_family = "wasm")]
+fn read_save() -> Option<String> {
+    local_storage()?.get_item(STORAGE_KEY).ok()?
+}
+
+#[cfg(target_family = "wasm")]
+fn write_save(contents: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("No local storage to save to");
+        return;
+    };
+    if storage.set_item(STORAGE_KEY, contents).is_err() {
+        warn!("Couldn't write save data to local storage");
+    }
+}
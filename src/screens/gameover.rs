@@ -1,10 +1,19 @@
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::demo::score::Score;
+use crate::demo::daily::DailyDay;
+use crate::demo::mode::{GameMode, RunClock};
+use crate::demo::player::PlayerCount;
+use crate::demo::puzzle::{MAX_STARS, PuzzleResult};
+use crate::demo::score::{PlayerScores, Score};
+use crate::demo::versus::VersusResult;
+use crate::save::{DailyBest, SaveData};
 use crate::screens::Screen;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::GameOver), spawn_game_over);
+    app.add_systems(
+        OnEnter(Screen::GameOver),
+        (record_high_score, spawn_game_over).chain(),
+    );
 
     app.add_systems(
         Update,
@@ -22,7 +31,138 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 struct GameOverTimer(pub f32);
 
-fn spawn_game_over(mut commands: Commands, current_score: Res<Score>) {
+/// Whether the run that just ended set a new best for its mode.
+#[derive(Resource)]
+struct NewHighScore(bool);
+
+fn record_high_score(
+    mut commands: Commands,
+    current_score: Res<Score>,
+    game_mode: Res<GameMode>,
+    daily_day: Res<DailyDay>,
+    mut save_data: ResMut<SaveData>,
+) {
+    // puzzle progress is saved as stars when the level ends, and versus
+    // scores are only bragging rights between the two players
+    if matches!(*game_mode, GameMode::Puzzle | GameMode::Versus) {
+        commands.insert_resource(NewHighScore(false));
+        return;
+    }
+    let is_new_best = save_data.high_scores.record(*game_mode, current_score.0);
+    commands.insert_resource(NewHighScore(is_new_best));
+
+    if *game_mode == GameMode::Daily {
+        let day = daily_day.0;
+        let beats_days_best = save_data
+            .daily_best
+            .is_none_or(|best| best.day != day || current_score.0 > best.score);
+        if beats_days_best {
+            save_data.daily_best = Some(DailyBest {
+                day,
+                score: current_score.0,
+            });
+        }
+    }
+}
+
+fn game_over_title(
+    game_mode: GameMode,
+    run_clock: &RunClock,
+    puzzle_result: Option<&PuzzleResult>,
+    versus_result: Option<&VersusResult>,
+) -> &'static str {
+    match game_mode {
+        GameMode::Versus => match versus_result.and_then(|result| result.winner) {
+            Some(0) => "P1 Wins",
+            Some(_) => "P2 Wins",
+            None => "Draw",
+        },
+        GameMode::Puzzle if puzzle_result.is_some_and(|result| result.cleared) => "Cleared",
+        GameMode::Puzzle => "Out of Shots",
+        GameMode::Endless | GameMode::Daily => "Game Over",
+        GameMode::TimeAttack if run_clock.is_out_of_time(game_mode) => "Time's Up",
+        GameMode::TimeAttack => "Game Over",
+        GameMode::Survival => "It Fell",
+    }
+}
+
+fn high_scores_text(
+    game_mode: GameMode,
+    new_high_score: bool,
+    save_data: &SaveData,
+    daily_day: DailyDay,
+    puzzle_result: Option<&PuzzleResult>,
+) -> String {
+    if game_mode == GameMode::Puzzle {
+        return match puzzle_result {
+            Some(result) if result.cleared => {
+                format!("{} of {} stars", result.stars, MAX_STARS)
+            }
+            _ => "give it another go".to_string(),
+        };
+    }
+    if game_mode == GameMode::Versus {
+        return "good game".to_string();
+    }
+    if game_mode == GameMode::Daily {
+        // the daily best is saved between sessions, so show that instead
+        let todays_best = save_data
+            .daily_best
+            .filter(|best| best.day == daily_day.0)
+            .map_or(0, |best| best.score);
+        return format!("today's best:  {todays_best}");
+    }
+    let scores = save_data
+        .high_scores
+        .get(game_mode)
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join("  ");
+    if new_high_score {
+        format!("new {} best!  {}", game_mode.name(), scores)
+    } else {
+        format!("{} best:  {}", game_mode.name(), scores)
+    }
+}
+
+/// The team score, plus who scored what in co-op. Versus only has the
+/// players' own scores.
+fn score_text(
+    game_mode: GameMode,
+    score: u32,
+    player_scores: &PlayerScores,
+    player_count: usize,
+) -> String {
+    if player_count < 2 {
+        return format!("{score} dang points,  wow");
+    }
+    let breakdown = player_scores.0[..player_count]
+        .iter()
+        .enumerate()
+        .map(|(index, score)| format!("P{} {}", index + 1, score))
+        .collect::<Vec<_>>()
+        .join("  ");
+    if game_mode == GameMode::Versus {
+        breakdown
+    } else {
+        format!("{score} dang points together  ({breakdown})")
+    }
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    current_score: Res<Score>,
+    player_scores: Res<PlayerScores>,
+    player_count: Res<PlayerCount>,
+    game_mode: Res<GameMode>,
+    run_clock: Res<RunClock>,
+    new_high_score: Res<NewHighScore>,
+    save_data: Res<SaveData>,
+    daily_day: Res<DailyDay>,
+    puzzle_result: Option<Res<PuzzleResult>>,
+    versus_result: Option<Res<VersusResult>>,
+) {
     commands.spawn((
         StateScoped(Screen::GameOver),
         Node {
@@ -42,7 +182,12 @@ fn spawn_game_over(mut commands: Commands, current_score: Res<Score>) {
                     padding: UiRect::all(Val::Px(16.0)),
                     ..default()
                 },
-                Text::new("Game Over"),
+                Text::new(game_over_title(
+                    *game_mode,
+                    &run_clock,
+                    puzzle_result.as_deref(),
+                    versus_result.as_deref()
+                )),
                 TextFont::from_font_size(22.0)
             ),
             (
@@ -50,9 +195,28 @@ fn spawn_game_over(mut commands: Commands, current_score: Res<Score>) {
                     padding: UiRect::all(Val::Px(12.0)),
                     ..default()
                 },
-                Text::new(format!("{} dang points,  wow", current_score.0)),
+                Text::new(score_text(
+                    *game_mode,
+                    current_score.0,
+                    &player_scores,
+                    player_count.for_mode(*game_mode)
+                )),
                 TextFont::from_font_size(20.0)
             ),
+            (
+                Node {
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                Text::new(high_scores_text(
+                    *game_mode,
+                    new_high_score.0,
+                    &save_data,
+                    *daily_day,
+                    puzzle_result.as_deref()
+                )),
+                TextFont::from_font_size(16.0)
+            ),
             (
                 Node {
                     padding: UiRect::all(Val::Px(10.0)),
@@ -1,20 +1,34 @@
 //! The screen state for the main gameplay.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    ui::Val::*,
+    window::{WindowFocused, WindowOccluded},
+};
 
-use crate::{Pause, demo::level::spawn_level, menus::Menu, screens::Screen};
+use crate::{Pause, demo::level::spawn_level, menus::Menu, net::NetSession, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
 
-    // Toggle pause on key press.
+    app.register_type::<AutoPause>();
+    app.init_resource::<AutoPause>();
+
+    // Toggle pause on key press, or when the window loses focus. Online games
+    // can't pause, since the other player's game would carry on without us.
     app.add_systems(
         Update,
         (
             (pause, spawn_pause_overlay, open_pause_menu).run_if(
                 in_state(Screen::Gameplay)
                     .and(in_state(Menu::None))
-                    .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape))),
+                    .and(not(resource_exists::<NetSession>))
+                    .and(
+                        input_just_pressed(KeyCode::KeyP)
+                            .or(input_just_pressed(KeyCode::Escape))
+                            .or(window_lost_focus),
+                    ),
             ),
             close_menu.run_if(
                 in_state(Screen::Gameplay)
@@ -30,6 +44,32 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
+/// Whether the game pauses itself when the window loses focus or gets hidden,
+/// like on alt-tab or switching browser tabs. Toggled from the settings menu,
+/// on by default.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct AutoPause {
+    pub enabled: bool,
+}
+
+impl Default for AutoPause {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+fn window_lost_focus(
+    mut ev_window_focused: EventReader<WindowFocused>,
+    mut ev_window_occluded: EventReader<WindowOccluded>,
+    auto_pause: Res<AutoPause>,
+) -> bool {
+    // read everything, so nothing stale is left to pause us later
+    let lost_focus = ev_window_focused.read().filter(|ev| !ev.focused).count() > 0;
+    let hidden = ev_window_occluded.read().filter(|ev| ev.occluded).count() > 0;
+    auto_pause.enabled && (lost_focus || hidden)
+}
+
 fn unpause(mut next_pause: ResMut<NextState<Pause>>) {
     next_pause.set(Pause(false));
 }
@@ -3,7 +3,10 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::ResourceHandles, screens::Screen, theme::prelude::*};
+use crate::{
+    asset_tracking::ResourceHandles, demo::tutorial::AfterTutorial, screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Loading), spawn_loading_screen);
@@ -22,8 +25,16 @@ fn spawn_loading_screen(mut commands: Commands) {
     ));
 }
 
-fn enter_gameplay_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Gameplay);
+fn enter_gameplay_screen(
+    after_tutorial: Option<Res<AfterTutorial>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    // whoever sent us here wanted the tutorial first
+    next_screen.set(if after_tutorial.is_some() {
+        Screen::Tutorial
+    } else {
+        Screen::Gameplay
+    });
 }
 
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
@@ -1,7 +1,7 @@
 //! The game's main screen states and transitions between them.
 
 mod gameover;
-mod gameplay;
+pub(super) mod gameplay;
 mod loading;
 mod splash;
 mod title;
@@ -30,4 +30,12 @@ pub enum Screen {
     Loading,
     Gameplay,
     GameOver,
+    /// The first-run tutorial, also replayable from the main menu.
+    Tutorial,
+}
+
+/// Run condition for systems that should run whenever the game is being
+/// played, whether that's a run or the tutorial.
+pub fn in_play(screen: Res<State<Screen>>) -> bool {
+    matches!(screen.get(), Screen::Gameplay | Screen::Tutorial)
 }
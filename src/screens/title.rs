@@ -2,15 +2,20 @@
 
 use bevy::prelude::*;
 
-use crate::{menus::Menu, screens::Screen};
+use crate::{demo::mode::GameMode, menus::Menu, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), open_main_menu);
     app.add_systems(OnExit(Screen::Title), close_menu);
 }
 
-fn open_main_menu(mut next_menu: ResMut<NextState<Menu>>) {
-    next_menu.set(Menu::Main);
+fn open_main_menu(game_mode: Res<GameMode>, mut next_menu: ResMut<NextState<Menu>>) {
+    // coming back from a puzzle, so go straight back to picking the next one
+    next_menu.set(if *game_mode == GameMode::Puzzle {
+        Menu::PuzzleSelect
+    } else {
+        Menu::Main
+    });
 }
 
 fn close_menu(mut next_menu: ResMut<NextState<Menu>>) {
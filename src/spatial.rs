@@ -0,0 +1,110 @@
+//! A uniform grid broadphase for proximity checks. Each grid holds one kind of
+//! thing (anything that can be set off, buildings, ...) and is rebuilt from
+//! scratch every frame, so a check only has to look at the few cells near it
+//! instead of every entity on screen.
+//!
+//! It lives in the library half of the crate, alongside `main.rs`, so
+//! `benches/spatial_grid.rs` can use it too.
+
+use std::marker::PhantomData;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+pub fn plugin<T: Hitbox>(app: &mut App) {
+    app.init_resource::<SpatialGrid<T>>();
+}
+
+/// Big enough that a typical explosion only covers a handful of cells.
+pub const DEFAULT_CELL_SIZE: f32 = 64.0;
+
+/// Something that takes up a circle in a [`SpatialGrid`].
+pub trait Hitbox: Component {
+    fn hitbox_radius(&self) -> f32;
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GridEntry {
+    entity: Entity,
+    position: Vec2,
+    radius: f32,
+}
+
+/// Every entity with a `T`, bucketed by which cell its centre is in.
+#[derive(Resource, Debug)]
+pub struct SpatialGrid<T: Hitbox> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<GridEntry>>,
+    /// The largest hitbox in the grid, so queries know how far past their own
+    /// radius to look.
+    max_radius: f32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Hitbox> Default for SpatialGrid<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl<T: Hitbox> SpatialGrid<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            max_radius: 0.0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Empties the grid but keeps the cells around, so rebuilding every frame
+    /// doesn't allocate once things have settled.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2, radius: f32) {
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().push(GridEntry {
+            entity,
+            position,
+            radius,
+        });
+        self.max_radius = self.max_radius.max(radius);
+    }
+
+    /// Entities whose hitbox overlaps the circle at `center`, touching counts.
+    pub fn query(&self, center: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        let reach = Vec2::splat(radius + self.max_radius);
+        let min = self.cell_of(center - reach);
+        let max = self.cell_of(center + reach);
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |entry| entry.position.distance(center) <= radius + entry.radius)
+            .map(|entry| entry.entity)
+    }
+
+    fn cell_of(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+}
+
+/// Refills the grid from wherever everything is right now. Run this right
+/// before the checks that use it.
+pub fn rebuild_grid<T: Hitbox>(
+    mut grid: ResMut<SpatialGrid<T>>,
+    query: Query<(Entity, &Transform, &T)>,
+) {
+    grid.clear();
+    for (entity, transform, hitbox) in &query {
+        grid.insert(
+            entity,
+            transform.translation.truncate(),
+            hitbox.hitbox_radius(),
+        );
+    }
+}
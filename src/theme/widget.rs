@@ -1,6 +1,6 @@
 //! Helper functions for creating common widgets.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, marker::PhantomData};
 
 use bevy::{
     ecs::{spawn::SpawnWith, system::IntoObserverSystem},
@@ -72,6 +72,26 @@ where
     )
 }
 
+/// A medium square button with text and an action defined as an [`Observer`].
+pub fn button_medium<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: Event,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        action,
+        Node {
+            width: Px(80.0),
+            height: Px(80.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+    )
+}
+
 /// A small square button with text and an action defined as an [`Observer`].
 pub fn button_small<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where
@@ -92,6 +112,117 @@ where
     )
 }
 
+/// A setting shown between "-" and "+" buttons, which run `lower` and
+/// `raise`. The value comes from the resource `R` through `show`, and is kept
+/// up to date by [`update_stepper_labels`].
+pub fn stepper<R, M1, M2, I1, I2>(
+    name: impl Into<String>,
+    lower: I1,
+    raise: I2,
+    show: impl Fn(&R) -> String + Send + Sync + 'static,
+) -> impl Bundle
+where
+    R: Resource,
+    I1: IntoObserverSystem<Pointer<Click>, (), M1>,
+    I2: IntoObserverSystem<Pointer<Click>, (), M2>,
+{
+    let name = name.into();
+    (
+        Name::new(format!("{name} Widget")),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            button_small("-", lower),
+            (
+                Name::new(format!("Current {name}")),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(
+                    label(""),
+                    StepperLabel {
+                        show: Box::new(show),
+                        _resource: PhantomData::<fn() -> R>,
+                    }
+                )],
+            ),
+            button_small("+", raise),
+        ],
+    )
+}
+
+/// A [`stepper`] that turns a setting off and on.
+pub fn toggle<R: Resource>(
+    name: impl Into<String>,
+    get: fn(&R) -> bool,
+    set: fn(&mut R, bool),
+) -> impl Bundle {
+    stepper(
+        name,
+        move |_: Trigger<Pointer<Click>>, mut value: ResMut<R>| set(&mut value, false),
+        move |_: Trigger<Pointer<Click>>, mut value: ResMut<R>| set(&mut value, true),
+        move |value: &R| if get(value) { "On" } else { "Off" }.to_string(),
+    )
+}
+
+/// A [`stepper`] that moves through a fixed list of options, stopping at
+/// either end.
+pub fn cycle<R, T>(
+    name: impl Into<String>,
+    options: &'static [T],
+    get: fn(&R) -> T,
+    set: fn(&mut R, T),
+    show: fn(T) -> String,
+) -> impl Bundle
+where
+    R: Resource,
+    T: Copy + PartialEq + Send + Sync + 'static,
+{
+    let index_of = move |value: &R| {
+        options
+            .iter()
+            .position(|&option| option == get(value))
+            .unwrap_or_default()
+    };
+    stepper(
+        name,
+        move |_: Trigger<Pointer<Click>>, mut value: ResMut<R>| {
+            let index = index_of(&value).saturating_sub(1);
+            set(&mut value, options[index]);
+        },
+        move |_: Trigger<Pointer<Click>>, mut value: ResMut<R>| {
+            let index = (index_of(&value) + 1).min(options.len() - 1);
+            set(&mut value, options[index]);
+        },
+        move |value: &R| show(get(value)),
+    )
+}
+
+/// The text of a [`stepper`], and how to show the value it steps through.
+#[derive(Component)]
+pub struct StepperLabel<R: Resource> {
+    show: Box<dyn Fn(&R) -> String + Send + Sync>,
+    _resource: PhantomData<fn() -> R>,
+}
+
+/// Keeps every [`stepper`] over `R` showing its current value. Has to be
+/// added once for each resource that menus step through.
+pub fn update_stepper_labels<R: Resource>(
+    value: Res<R>,
+    mut label_query: Query<(&mut Text, &StepperLabel<R>)>,
+) {
+    for (mut text, label) in &mut label_query {
+        let shown = (label.show)(&value);
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+}
+
 /// A simple button with text and an action defined as an [`Observer`]. The button's layout is provided by `button_bundle`.
 fn button_base<E, B, M, I>(
     text: impl Into<String>,